@@ -1,15 +1,24 @@
 use clap::Parser;
 use clap::ValueEnum;
 use fishrambeta::math::steps::StepLogger;
-use fishrambeta::math::{Equation, Variable};
+use fishrambeta::math::{Assumptions, Equation, RuleSet, SimplifyOptions, Variable};
+use fishrambeta::parser::ParseError;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::io::IsTerminal;
 
 #[derive(Parser, Debug)]
 pub struct Args {
-    //The equation to solve formatted in LaTeX
+    //The equation to solve formatted in LaTeX or plain text
     #[arg(short, long)]
     equation: String,
+    //The format of the equation, detected from the input by default
+    #[arg(long, value_enum, default_value_t = InputFormat::Auto)]
+    input_format: InputFormat,
+    //The format to print the resulting equation in, pretty printed on a terminal and LaTeX
+    //followed by NumPy otherwise if not given
+    #[arg(long, value_enum)]
+    output_format: Option<OutputFormat>,
     //The thing to do with the equation
     #[arg(short, long, value_enum)]
     operation: Operation,
@@ -20,6 +29,19 @@ pub struct Args {
     //Assume consecutive letters multiply variables with those names
     #[arg(long, default_value_t = false)]
     implicit_multiplication: bool,
+    //A file with rewrite rules like `name: lhs -> rhs` to simplify the result with
+    #[arg(long)]
+    rules: Option<String>,
+    //What is known about the variables, like `m > 0, n \in \mathbb{Z}, x \neq 0`
+    #[arg(long, default_value = "")]
+    assume: String,
+    //Don't assume letters are real either, only rely on the assumptions
+    #[arg(long, default_value_t = false, conflicts_with = "lenient")]
+    strict: bool,
+    //Treat everything that isn't exactly zero as nonzero, so `\frac{x}{x}` is cancelled without
+    //assuming `x \neq 0`
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
     //Variables to propagate errors of, seperated by commas
     #[arg(long, default_value = "")]
     error_variables: String,
@@ -34,6 +56,62 @@ enum Operation {
     Integrate,
     Error,
     TaylorSeries,
+    Expand,
+    Factor,
+    Collect,
+    Together,
+    Apart,
+    TrigExpand,
+    TrigReduce,
+    ExpandLog,
+    CombineLog,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+enum InputFormat {
+    Auto,
+    Latex,
+    Text,
+}
+
+impl InputFormat {
+    /// Picks LaTeX when the input uses backslash commands or braces, and plain text otherwise
+    fn detect(input: &str) -> InputFormat {
+        if input.contains(['\\', '{', '}']) {
+            InputFormat::Latex
+        } else {
+            InputFormat::Text
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+enum OutputFormat {
+    Pretty,
+    Latex,
+    Typst,
+    Mathml,
+    Numpy,
+    Sympy,
+    Wolfram,
+    Julia,
+    Dot,
+}
+
+impl OutputFormat {
+    fn format(self, equation: &Equation) -> String {
+        match self {
+            OutputFormat::Pretty => equation.to_pretty(),
+            OutputFormat::Latex => equation.to_latex(),
+            OutputFormat::Typst => equation.to_typst(),
+            OutputFormat::Mathml => equation.to_mathml(),
+            OutputFormat::Numpy => equation.to_numpy(),
+            OutputFormat::Sympy => equation.to_sympy(),
+            OutputFormat::Wolfram => equation.to_wolfram(),
+            OutputFormat::Julia => equation.to_julia(),
+            OutputFormat::Dot => equation.to_dot(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -53,7 +131,37 @@ impl fmt::Display for Result {
 
 fn main() {
     let args = Args::parse();
-    let equation = Equation::from_latex(&args.equation, args.implicit_multiplication);
+    let input_format = match args.input_format {
+        InputFormat::Auto => InputFormat::detect(&args.equation),
+        input_format => input_format,
+    };
+    let parsed = match input_format {
+        InputFormat::Text => Equation::try_from_text(&args.equation),
+        _ => Equation::try_from_latex(&args.equation, args.implicit_multiplication),
+    };
+    let equation = match parsed {
+        Ok(equation) => equation,
+        Err(error) => {
+            print_parse_error(&args.equation, &error);
+            std::process::exit(1);
+        }
+    };
+    let rules = match args.rules.as_deref().map(read_rules).transpose() {
+        Ok(rules) => rules,
+        Err(error) => {
+            eprintln!("error: {error}");
+            std::process::exit(1);
+        }
+    };
+    let assumptions = match Assumptions::parse(&args.assume) {
+        Ok(assumptions) if args.strict => assumptions.into_strict(),
+        Ok(assumptions) if args.lenient => assumptions.into_lenient(),
+        Ok(assumptions) => assumptions,
+        Err(error) => {
+            eprintln!("error: {error}");
+            std::process::exit(1);
+        }
+    };
     println!("Input equation: {}", equation);
 
     use std::time::Instant;
@@ -65,47 +173,90 @@ fn main() {
         args.operation,
         &value_dict,
         &args.error_variables,
+        &assumptions,
         &mut step_logger,
     );
+    let result = match (result, &rules) {
+        (Result::Equation(equation), Some(rules)) => {
+            Result::Equation(equation.simplify_with_rules(rules, &mut step_logger))
+        }
+        (result, _) => result,
+    };
     let elapsed = now.elapsed();
     println!("{}", step_logger.unwrap());
     println!("Elapsed: {:.2?}", elapsed);
-    println!("{}", result);
-    if let Result::Equation(result) = result {
-        println!("{}", result.to_numpy());
+    match (result, args.output_format) {
+        (Result::Equation(result), Some(output_format)) => {
+            println!("{}", output_format.format(&result));
+        }
+        (Result::Equation(result), None) if std::io::stdout().is_terminal() => {
+            println!("{}", result.to_pretty());
+        }
+        (Result::Equation(result), None) => {
+            println!("{}", result);
+            println!("{}", result.to_numpy());
+        }
+        (result, _) => println!("{}", result),
     }
 }
 
+fn read_rules(path: &str) -> std::result::Result<RuleSet, String> {
+    let text = std::fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+    RuleSet::parse(&text).map_err(|error| format!("{path}: {error}"))
+}
+
+fn print_parse_error(input: &str, error: &ParseError) {
+    let offset = input[..error.span.start].chars().count();
+    let width = input[error.span.clone()].chars().count().max(1);
+    eprintln!("error: {error}");
+    eprintln!("  {input}");
+    eprintln!("  {}{}", " ".repeat(offset), "^".repeat(width));
+}
+
 fn process_operation(
     equation: Equation,
     operation: Operation,
     value_dict: &BTreeMap<Variable, f64>,
     error_variables: &str,
+    assumptions: &Assumptions,
     step_logger: &mut Option<StepLogger>,
 ) -> Result {
     match operation {
         Operation::Simplify => {
-            let mut equation = equation.clone();
-            equation = equation.simplify_until_complete_with_print(step_logger);
-            println!("{}", equation.to_numpy());
-            Result::Equation(equation)
+            let options = SimplifyOptions {
+                assumptions: assumptions.clone(),
+                ..SimplifyOptions::default()
+            };
+            let simplified = equation.simplify_with_options(&options, step_logger);
+            if !simplified.reached_fixed_point() {
+                eprintln!(
+                    "warning: stopped simplifying before reaching a fixed point ({:?})",
+                    simplified.convergence
+                );
+            }
+            println!("{}", simplified.equation.to_numpy());
+            Result::Equation(simplified.equation)
         }
         Operation::Calculate => Result::Value(equation.calculate(value_dict)),
         Operation::Differentiate => {
-            let mut equation = equation
-                .clone()
-                .differentiate(&Variable::Letter("x".to_string()), step_logger);
+            let mut equation = equation.clone().differentiate_with_assumptions(
+                &Variable::Letter("x".to_string()),
+                assumptions,
+                step_logger,
+            );
             println!("Unsimplified: {}", equation);
-            equation = equation.simplify_until_complete_with_print(step_logger);
+            equation = equation.simplify_until_complete_with_assumptions(assumptions, step_logger);
             Result::Equation(equation)
         }
         Operation::Integrate => {
             println!("Start integrate");
-            let mut equation = equation
-                .clone()
-                .integrate(&Variable::Letter("x".to_string()), step_logger);
+            let mut equation = equation.clone().integrate_with_assumptions(
+                &Variable::Letter("x".to_string()),
+                assumptions,
+                step_logger,
+            );
             println!("Unsimplified: {}", equation);
-            equation = equation.simplify_until_complete_with_print(step_logger);
+            equation = equation.simplify_until_complete_with_assumptions(assumptions, step_logger);
             Result::Equation(equation)
         }
         Operation::Error => {
@@ -116,7 +267,7 @@ fn process_operation(
             Result::Equation(
                 equation
                     .error_analysis(variables, step_logger)
-                    .simplify_until_complete_with_print(step_logger),
+                    .simplify_until_complete_with_assumptions(assumptions, step_logger),
             )
         }
         Operation::TaylorSeries => Result::Equation(
@@ -129,6 +280,19 @@ fn process_operation(
                 )
                 .into_equation(),
         ),
+        Operation::Expand => Result::Equation(equation.expand(step_logger)),
+        Operation::Factor => Result::Equation(equation.factor(step_logger)),
+        Operation::Collect => {
+            Result::Equation(equation.collect(&Variable::Letter("x".to_string()), step_logger))
+        }
+        Operation::Together => Result::Equation(equation.together(step_logger)),
+        Operation::Apart => {
+            Result::Equation(equation.apart(&Variable::Letter("x".to_string()), step_logger))
+        }
+        Operation::TrigExpand => Result::Equation(equation.trig_expand(step_logger)),
+        Operation::TrigReduce => Result::Equation(equation.trig_reduce(step_logger)),
+        Operation::ExpandLog => Result::Equation(equation.expand_log(assumptions, step_logger)),
+        Operation::CombineLog => Result::Equation(equation.combine_log(assumptions, step_logger)),
         _ => {
             panic!("Operation not yet supported")
         }
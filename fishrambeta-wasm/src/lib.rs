@@ -10,22 +10,27 @@ use wasm_bindgen::prelude::*;
 #[derive(Serialize, Deserialize)]
 pub struct Result {
     latex: String,
+    typst: String,
     numpy: String,
     steps: Vec<String>,
 }
 
 #[wasm_bindgen]
-pub fn simplify(equation: &str, implicit_multiplication: bool) -> JsValue {
-    let parsed = Equation::from_latex(equation, implicit_multiplication);
+pub fn simplify(
+    equation: &str,
+    implicit_multiplication: bool,
+) -> std::result::Result<JsValue, JsError> {
+    let parsed = parse(equation, implicit_multiplication)?;
     let mut step_logger = Some(StepLogger::new());
     let simplified = parsed.simplify_until_complete(&mut step_logger);
 
-    serde_wasm_bindgen::to_value(&Result {
+    Ok(serde_wasm_bindgen::to_value(&Result {
         latex: simplified.to_latex(),
+        typst: simplified.to_typst(),
         numpy: simplified.to_numpy(),
         steps: step_logger.unwrap().get_steps_as_strings(),
     })
-    .unwrap()
+    .unwrap())
 }
 
 #[wasm_bindgen]
@@ -33,8 +38,8 @@ pub fn differentiate(
     equation: &str,
     differentiate_to: &str,
     implicit_multiplication: bool,
-) -> JsValue {
-    let parsed = Equation::from_latex(equation, implicit_multiplication);
+) -> std::result::Result<JsValue, JsError> {
+    let parsed = parse(equation, implicit_multiplication)?;
     let mut step_logger = Some(StepLogger::new());
     let differentiated = parsed
         .differentiate(
@@ -42,17 +47,22 @@ pub fn differentiate(
             &mut step_logger,
         )
         .simplify_until_complete(&mut step_logger);
-    serde_wasm_bindgen::to_value(&Result {
+    Ok(serde_wasm_bindgen::to_value(&Result {
         latex: differentiated.to_latex(),
+        typst: differentiated.to_typst(),
         numpy: differentiated.to_numpy(),
         steps: step_logger.unwrap().get_steps_as_strings(),
     })
-    .unwrap()
+    .unwrap())
 }
 
 #[wasm_bindgen]
-pub fn integrate(equation: &str, integrate_to: &str, implicit_multiplication: bool) -> JsValue {
-    let parsed = Equation::from_latex(equation, implicit_multiplication);
+pub fn integrate(
+    equation: &str,
+    integrate_to: &str,
+    implicit_multiplication: bool,
+) -> std::result::Result<JsValue, JsError> {
+    let parsed = parse(equation, implicit_multiplication)?;
     let mut step_logger = Some(StepLogger::new());
     let integrated = parsed
         .integrate(
@@ -61,12 +71,13 @@ pub fn integrate(equation: &str, integrate_to: &str, implicit_multiplication: bo
         )
         .simplify_until_complete(&mut step_logger);
 
-    serde_wasm_bindgen::to_value(&Result {
+    Ok(serde_wasm_bindgen::to_value(&Result {
         latex: integrated.to_latex(),
+        typst: integrated.to_typst(),
         numpy: integrated.to_numpy(),
         steps: step_logger.unwrap().get_steps_as_strings(),
     })
-    .unwrap()
+    .unwrap())
 }
 
 #[wasm_bindgen]
@@ -75,23 +86,24 @@ pub fn calculate(
     user_values_keys: &str,
     user_values_values: &[f64],
     implicit_multiplication: bool,
-) -> JsValue {
+) -> std::result::Result<JsValue, JsError> {
     let mut values = physicsvalues::physics_values();
     let user_values_hashmap = user_values_to_hashmap(
         user_values_keys.split("\\n\\n").collect::<Vec<_>>(),
         user_values_values,
     );
     values.extend(user_values_hashmap);
-    let parsed: Equation = Equation::from_latex(equation, implicit_multiplication);
+    let parsed: Equation = parse(equation, implicit_multiplication)?;
     let result = parsed.calculate(&values);
-    serde_wasm_bindgen::to_value(&Result {
+    Ok(serde_wasm_bindgen::to_value(&Result {
         latex: result.to_string(),
+        typst: result.to_string(),
         numpy: result.to_string(),
         steps: vec![
             "\\textbf{I hope you know how to fill in variables in an equation...}".to_string(),
         ],
     })
-    .unwrap()
+    .unwrap())
 }
 
 #[wasm_bindgen]
@@ -101,9 +113,9 @@ pub fn taylor_expansion(
     around: &str,
     degree: i32,
     implicit_multiplication: bool,
-) -> JsValue {
-    let parsed = Equation::from_latex(equation, implicit_multiplication);
-    let around = Equation::from_latex(around, implicit_multiplication);
+) -> std::result::Result<JsValue, JsError> {
+    let parsed = parse(equation, implicit_multiplication)?;
+    let around = parse(around, implicit_multiplication)?;
     let mut step_logger = Some(StepLogger::new());
     let taylor_expansion = parsed
         .taylor_expansion(
@@ -114,12 +126,13 @@ pub fn taylor_expansion(
         )
         .into_equation()
         .simplify_until_complete(&mut step_logger);
-    serde_wasm_bindgen::to_value(&Result {
+    Ok(serde_wasm_bindgen::to_value(&Result {
         latex: taylor_expansion.to_latex(),
+        typst: taylor_expansion.to_typst(),
         numpy: taylor_expansion.to_numpy(),
         steps: step_logger.unwrap().get_steps_as_strings(),
     })
-    .unwrap()
+    .unwrap())
 }
 
 #[wasm_bindgen]
@@ -127,8 +140,8 @@ pub fn error_analysis(
     equation: &str,
     error_variables: &str,
     implicit_multiplication: bool,
-) -> JsValue {
-    let parsed: Equation = Equation::from_latex(equation, implicit_multiplication);
+) -> std::result::Result<JsValue, JsError> {
+    let parsed: Equation = parse(equation, implicit_multiplication)?;
     let mut step_logger = Some(StepLogger::new());
     let error_variables: Vec<_> = error_variables
         .split("\\n\\n")
@@ -137,12 +150,18 @@ pub fn error_analysis(
     let errors = parsed
         .error_analysis(error_variables, &mut step_logger)
         .simplify_until_complete(&mut step_logger);
-    serde_wasm_bindgen::to_value(&Result {
+    Ok(serde_wasm_bindgen::to_value(&Result {
         latex: errors.to_latex(),
+        typst: errors.to_typst(),
         numpy: errors.to_numpy(),
         steps: step_logger.unwrap().get_steps_as_strings(),
     })
-    .unwrap()
+    .unwrap())
+}
+
+fn parse(latex: &str, implicit_multiplication: bool) -> std::result::Result<Equation, JsError> {
+    Equation::try_from_latex(latex, implicit_multiplication)
+        .map_err(|error| JsError::new(&error.to_string()))
 }
 
 fn user_values_to_hashmap(keys: Vec<&str>, values: &[f64]) -> HashMap<Variable, f64> {
@@ -0,0 +1,269 @@
+use crate::math::{Equation, Variable};
+use num_rational::Rational64;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// A property a variable is assumed to have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Assumption {
+    /// `x > 0`, which implies real and nonzero
+    Positive,
+    /// `x \in \mathbb{R}`
+    Real,
+    /// `n \in \mathbb{Z}`, which implies real
+    Integer,
+    /// `m \neq 0`
+    Nonzero,
+}
+
+/// What is known about the variables of an equation, which decides whether simplifications like
+/// `\sqrt{x^{2}} = x` or cancelling `\frac{x}{x}` are valid
+///
+/// By default letters are real numbers and nothing else is known about them, so `\frac{x}{x}`
+/// is only cancelled when `x \neq 0` is assumed. [`Assumptions::strict`] doesn't assume letters
+/// are real either, and [`Assumptions::lenient`] cancels like ordinary algebra does.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Assumptions {
+    variables: BTreeMap<Variable, BTreeSet<Assumption>>,
+    mode: Mode,
+}
+
+/// What holds for every letter on top of what is assumed about it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Mode {
+    /// Letters are real and every expression that isn't exactly zero is nonzero
+    Lenient,
+    /// Letters are real
+    #[default]
+    Real,
+    /// Nothing
+    Strict,
+}
+
+/// An assumption that isn't of the form `x > 0`, `n \in \mathbb{Z}`, `x \in \mathbb{R}` or
+/// `m \neq 0`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssumptionError {
+    pub assumption: String,
+}
+
+impl Assumptions {
+    pub fn new() -> Self {
+        Assumptions::default()
+    }
+
+    /// Assumptions that only hold what is assumed, so letters aren't even real unless assumed
+    pub fn strict() -> Self {
+        Assumptions::new().into_strict()
+    }
+
+    /// Assumptions under which every expression that isn't exactly zero is nonzero, so
+    /// `\frac{x}{x}` becomes 1 like in ordinary algebra, even though that is wrong at `x = 0`
+    pub fn lenient() -> Self {
+        Assumptions::new().into_lenient()
+    }
+
+    /// The same assumptions, made strict, see [`Assumptions::strict`]
+    pub fn into_strict(self) -> Self {
+        Assumptions {
+            mode: Mode::Strict,
+            ..self
+        }
+    }
+
+    /// The same assumptions, made lenient, see [`Assumptions::lenient`]
+    pub fn into_lenient(self) -> Self {
+        Assumptions {
+            mode: Mode::Lenient,
+            ..self
+        }
+    }
+
+    /// Assumes the letter `name` has the property
+    pub fn assume(mut self, name: &str, assumption: Assumption) -> Self {
+        self.variables
+            .entry(Variable::Letter(name.to_string()))
+            .or_default()
+            .insert(assumption);
+        self
+    }
+
+    /// Parses assumptions separated by commas, like `m > 0, n \in \mathbb{Z}, x \neq 0`. The
+    /// symbols `∈`, `ℤ`, `ℝ` and `≠` may be written as Unicode as well.
+    ///
+    /// # Errors
+    /// Returns an [`AssumptionError`] for the first assumption that can't be parsed
+    pub fn parse(text: &str) -> Result<Assumptions, AssumptionError> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|assumption| !assumption.is_empty())
+            .try_fold(Assumptions::new(), |assumptions, assumption| {
+                let (name, property) =
+                    parse_assumption(assumption).ok_or_else(|| AssumptionError {
+                        assumption: assumption.to_string(),
+                    })?;
+                Ok(assumptions.assume(&name, property))
+            })
+    }
+
+    fn holds(&self, variable: &Variable, assumption: Assumption) -> bool {
+        let Some(assumed) = self.variables.get(variable) else {
+            return false;
+        };
+        assumed.contains(&assumption)
+            || match assumption {
+                Assumption::Real => {
+                    assumed.contains(&Assumption::Positive)
+                        || assumed.contains(&Assumption::Integer)
+                }
+                Assumption::Nonzero => assumed.contains(&Assumption::Positive),
+                Assumption::Positive | Assumption::Integer => false,
+            }
+    }
+
+    /// Whether the equation is known to be greater than zero
+    pub fn is_positive(&self, equation: &Equation) -> bool {
+        if let Some(number) = equation.calculate_exact() {
+            return number > Rational64::from(0);
+        }
+        match equation {
+            Equation::Variable(Variable::Constant(_)) => true,
+            Equation::Variable(variable) => self.holds(variable, Assumption::Positive),
+            Equation::Addition(terms) | Equation::Multiplication(terms) => {
+                terms.iter().all(|term| self.is_positive(term))
+            }
+            Equation::Division(division) => {
+                self.is_positive(&division.0) && self.is_positive(&division.1)
+            }
+            Equation::Power(power) => {
+                (self.is_positive(&power.0) && self.is_real(&power.1))
+                    || (self.is_real(&power.0)
+                        && self.is_nonzero(&power.0)
+                        && is_even_integer(&power.1))
+            }
+            Equation::Abs(abs) => self.is_nonzero(abs),
+            _ => false,
+        }
+    }
+
+    /// Whether the equation is known not to be zero
+    pub fn is_nonzero(&self, equation: &Equation) -> bool {
+        if let Some(number) = equation.calculate_exact() {
+            return number != Rational64::from(0);
+        }
+        if self.mode == Mode::Lenient {
+            return true;
+        }
+        match equation {
+            Equation::Variable(Variable::Constant(_)) => true,
+            Equation::Variable(variable) => self.holds(variable, Assumption::Nonzero),
+            Equation::Negative(e) | Equation::Abs(e) => self.is_nonzero(e),
+            Equation::Multiplication(factors) => factors.iter().all(|f| self.is_nonzero(f)),
+            Equation::Division(division) => self.is_nonzero(&division.0),
+            Equation::Power(power) => self.is_nonzero(&power.0),
+            equation => self.is_positive(equation),
+        }
+    }
+
+    /// Whether the equation is known to be a real number
+    pub fn is_real(&self, equation: &Equation) -> bool {
+        match equation {
+            Equation::Variable(Variable::Letter(_)) if self.mode != Mode::Strict => true,
+            Equation::Variable(variable @ Variable::Letter(_)) => {
+                self.holds(variable, Assumption::Real)
+            }
+            Equation::Variable(Variable::Vector(_)) => false,
+            Equation::Variable(_) | Equation::Abs(_) => true,
+            Equation::Negative(e) | Equation::Sin(e) | Equation::Cos(e) | Equation::Arctan(e) => {
+                self.is_real(e)
+            }
+            Equation::Addition(terms) | Equation::Multiplication(terms) => {
+                terms.iter().all(|term| self.is_real(term))
+            }
+            Equation::Division(division) => self.is_real(&division.0) && self.is_real(&division.1),
+            Equation::Power(power) => {
+                (self.is_positive(&power.0) && self.is_real(&power.1))
+                    || (self.is_real(&power.0) && self.is_integer(&power.1))
+            }
+            Equation::Ln(ln) => self.is_positive(ln),
+            _ => false,
+        }
+    }
+
+    /// Whether the equation is known to be an integer
+    pub fn is_integer(&self, equation: &Equation) -> bool {
+        if let Some(number) = equation.calculate_exact() {
+            return number.is_integer();
+        }
+        match equation {
+            Equation::Variable(variable) => self.holds(variable, Assumption::Integer),
+            Equation::Negative(e) => self.is_integer(e),
+            Equation::Addition(terms) | Equation::Multiplication(terms) => {
+                terms.iter().all(|term| self.is_integer(term))
+            }
+            Equation::Power(power) => {
+                self.is_integer(&power.0)
+                    && power
+                        .1
+                        .calculate_exact()
+                        .is_some_and(|exponent| exponent.is_integer() && exponent >= 0.into())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the equation is known not to be negative
+    pub fn is_nonnegative(&self, equation: &Equation) -> bool {
+        if let Some(number) = equation.calculate_exact() {
+            return number >= Rational64::from(0);
+        }
+        match equation {
+            Equation::Abs(_) => true,
+            Equation::Power(power) if self.is_real(&power.0) && is_even_integer(&power.1) => true,
+            Equation::Multiplication(factors) => factors.iter().all(|f| self.is_nonnegative(f)),
+            Equation::Addition(terms) => terms.iter().all(|term| self.is_nonnegative(term)),
+            equation => self.is_positive(equation),
+        }
+    }
+}
+
+fn is_even_integer(equation: &Equation) -> bool {
+    equation
+        .calculate_exact()
+        .is_some_and(|number| number.is_integer() && number.to_integer() % 2 == 0)
+}
+
+fn parse_assumption(assumption: &str) -> Option<(String, Assumption)> {
+    let assumption = assumption.replace("\\in", "∈").replace("\\neq", "≠");
+    let (name, property) = if let Some((name, set)) = assumption.split_once('∈') {
+        let property = match set.trim() {
+            "\\mathbb{Z}" | "ℤ" => Assumption::Integer,
+            "\\mathbb{R}" | "ℝ" => Assumption::Real,
+            _ => return None,
+        };
+        (name, property)
+    } else if let Some((name, zero)) = assumption.split_once('≠') {
+        (zero.trim() == "0").then_some((name, Assumption::Nonzero))?
+    } else if let Some((name, zero)) = assumption.split_once('>') {
+        (zero.trim() == "0").then_some((name, Assumption::Positive))?
+    } else {
+        return None;
+    };
+    match Equation::try_from_latex(name.trim(), false) {
+        Ok(Equation::Variable(Variable::Letter(name))) => Some((name, property)),
+        _ => None,
+    }
+}
+
+impl fmt::Display for AssumptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid assumption '{}', expected one like 'x > 0', 'n \\in \\mathbb{{Z}}', \
+             'x \\in \\mathbb{{R}}' or 'm \\neq 0'",
+            self.assumption
+        )
+    }
+}
+
+impl std::error::Error for AssumptionError {}
@@ -17,14 +17,12 @@ impl Equation {
             }
             Equation::Negative(negative) => Some(-negative.calculate_exact()?),
             Equation::Addition(addition) => {
-                return addition.iter().map(super::Equation::calculate_exact).sum()
-            }
-            Equation::Multiplication(multiplication) => {
-                return multiplication
-                    .iter()
-                    .map(super::Equation::calculate_exact)
-                    .product()
+                addition.iter().map(super::Equation::calculate_exact).sum()
             }
+            Equation::Multiplication(multiplication) => multiplication
+                .iter()
+                .map(super::Equation::calculate_exact)
+                .product(),
             Equation::Division(division) => {
                 let numerator = division.0.calculate_exact();
                 let denominator = division.1.calculate_exact();
@@ -68,22 +66,7 @@ impl Equation {
                 }
                 None
             }
-            Equation::Sin(t) => {
-                if let Some(i) = t.calculate_exact() {
-                    if i == 0.into() {
-                        return Some(0.into());
-                    }
-                }
-                None
-            }
-            Equation::Cos(t) => {
-                if let Some(i) = t.calculate_exact() {
-                    if i == 0.into() {
-                        return Some(1.into());
-                    }
-                }
-                None
-            }
+            Equation::Sin(_) | Equation::Cos(_) => self.exact_trig_value()?.calculate_exact(),
             _ => None,
         }
     }
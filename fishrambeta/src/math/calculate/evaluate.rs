@@ -1,45 +1,44 @@
 use super::{Equation, Variable};
+use std::collections::HashMap;
 
 impl Equation {
     pub fn evaluate(self, variable: &Variable, replacement: &Equation) -> Equation {
+        self.substituted(&HashMap::from([(variable.clone(), replacement.clone())]))
+    }
+
+    /// Replaces all variables at once, so swapping `x` and `y` doesn't turn both into the same
+    /// variable. A derivative taken to a substituted variable is worked out before substituting.
+    pub fn substitute(&self, substitutions: &HashMap<Variable, Equation>) -> Equation {
+        self.clone().substituted(substitutions)
+    }
+
+    fn substituted(self, substitutions: &HashMap<Variable, Equation>) -> Equation {
         match self {
-            Equation::Variable(t) => {
-                if t == *variable {
-                    replacement.clone()
-                } else {
-                    Equation::Variable(t)
-                }
-            }
-            Equation::Negative(t) => {
-                Equation::Negative(Box::new(t.evaluate(variable, replacement)))
-            }
-            Equation::Addition(t) => Equation::Addition(
-                t.into_iter()
-                    .map(|t| t.evaluate(variable, replacement))
-                    .collect(),
-            ),
-            Equation::Multiplication(t) => Equation::Multiplication(
-                t.into_iter()
-                    .map(|t| t.evaluate(variable, replacement))
-                    .collect(),
-            ),
-            Equation::Division(t) => Equation::Division(Box::new((
-                t.0.evaluate(variable, replacement),
-                t.1.evaluate(variable, replacement),
-            ))),
-            Equation::Power(t) => Equation::Power(Box::new((
-                t.0.evaluate(variable, replacement),
-                t.1.evaluate(variable, replacement),
-            ))),
-            Equation::Ln(t) => Equation::Ln(Box::new(t.evaluate(variable, replacement))),
-            Equation::Sin(t) => Equation::Sin(Box::new(t.evaluate(variable, replacement))),
-            Equation::Cos(t) => Equation::Cos(Box::new(t.evaluate(variable, replacement))),
-            Equation::Arcsin(t) => Equation::Arcsin(Box::new(t.evaluate(variable, replacement))),
-            Equation::Arccos(t) => Equation::Arccos(Box::new(t.evaluate(variable, replacement))),
-            Equation::Arctan(t) => Equation::Arctan(Box::new(t.evaluate(variable, replacement))),
-            Equation::Abs(t) => Equation::Abs(Box::new(t.evaluate(variable, replacement))),
-            Equation::Derivative(_) => panic!(),
-            Equation::Equals(_) => panic!(),
+            Equation::Variable(variable) => match substitutions.get(&variable) {
+                Some(replacement) => replacement.clone(),
+                None => Equation::Variable(variable),
+            },
+            Equation::Derivative((derivative, is_partial)) => match &derivative.1 {
+                Equation::Variable(variable) if substitutions.contains_key(variable) => derivative
+                    .0
+                    .differentiate(variable, &mut None)
+                    .substituted(substitutions),
+                _ => Equation::Derivative((derivative, is_partial))
+                    .map_children(|child| child.substituted(substitutions)),
+            },
+            equation => equation.map_children(|child| child.substituted(substitutions)),
+        }
+    }
+
+    /// Replaces every occurrence of the subtree `pattern`, like `\sin(x)^2`, with `replacement`
+    pub fn replace_subexpression(&self, pattern: &Equation, replacement: &Equation) -> Equation {
+        self.clone().replaced(pattern, replacement)
+    }
+
+    fn replaced(self, pattern: &Equation, replacement: &Equation) -> Equation {
+        if self == *pattern {
+            return replacement.clone();
         }
+        self.map_children(|child| child.replaced(pattern, replacement))
     }
 }
@@ -18,11 +18,9 @@ impl Equation {
                 values[variable]
             }
             Equation::Negative(negative) => -negative.calculate(values),
-            Equation::Addition(addition) => {
-                return addition.iter().map(|x| x.calculate(values)).sum()
-            }
+            Equation::Addition(addition) => addition.iter().map(|x| x.calculate(values)).sum(),
             Equation::Multiplication(multiplication) => {
-                return multiplication.iter().map(|x| x.calculate(values)).product()
+                multiplication.iter().map(|x| x.calculate(values)).product()
             }
             Equation::Division(division) => {
                 division.0.calculate(values) / division.1.calculate(values)
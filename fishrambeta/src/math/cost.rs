@@ -0,0 +1,38 @@
+//! Cost functions for [`Equation::simplify_saturate`], lower is simpler
+use crate::math::Equation;
+
+/// What a negative exponent costs on top of the nodes of the equation
+const NEGATIVE_EXPONENT_PENALTY: usize = 100;
+
+/// The number of subexpressions, so `x\cdot (y+z)` costs 5
+pub fn node_count(equation: &Equation) -> usize {
+    equation.iter_subexpressions().count()
+}
+
+/// The length of the longest path from the root to a leaf, so `x\cdot (y+z)` costs 3
+pub fn depth(equation: &Equation) -> usize {
+    1 + equation
+        .children()
+        .into_iter()
+        .map(depth)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The node count, with every power to a negative exponent costing more than any form without
+/// one, so `x^{-2}` becomes `\frac{1}{x^{2}}`
+pub fn no_negative_exponents(equation: &Equation) -> usize {
+    let negative_exponents = equation
+        .iter_subexpressions()
+        .filter(|subexpression| match subexpression {
+            Equation::Power(power) => power
+                .1
+                .calculate_exact()
+                .map_or(matches!(power.1, Equation::Negative(_)), |exponent| {
+                    exponent < 0.into()
+                }),
+            _ => false,
+        })
+        .count();
+    node_count(equation) + NEGATIVE_EXPONENT_PENALTY * negative_exponents
+}
@@ -43,41 +43,11 @@ impl Equation {
             return self;
         }
         match self {
-            Equation::Variable(v) => {
-                if variables.contains(&v) {
-                    Equation::Variable(Variable::Integer(0))
-                } else {
-                    Equation::Variable(v)
-                }
+            Equation::Variable(v) if variables.contains(&v) => {
+                Equation::Variable(Variable::Integer(0))
             }
-            Equation::Negative(n) => Equation::Negative(Box::new(n.constant_part(variables))),
-            Equation::Addition(a) => Equation::Addition(
-                a.into_iter()
-                    .map(|term| term.constant_part(variables))
-                    .collect(),
-            ),
-            Equation::Multiplication(m) => Equation::Multiplication(
-                m.into_iter()
-                    .map(|term| term.constant_part(variables))
-                    .collect(),
-            ),
-            Equation::Division(d) => Equation::Division(Box::new((
-                d.0.constant_part(variables),
-                d.1.constant_part(variables),
-            ))),
-            Equation::Power(p) => Equation::Power(Box::new((
-                p.0.constant_part(variables),
-                p.1.constant_part(variables),
-            ))),
-            Equation::Ln(p) => Equation::Ln(Box::new(p.constant_part(variables))),
-            Equation::Sin(p) => Equation::Sin(Box::new(p.constant_part(variables))),
-            Equation::Cos(p) => Equation::Cos(Box::new(p.constant_part(variables))),
-            Equation::Abs(p) => Equation::Abs(Box::new(p.constant_part(variables))),
-            Equation::Arcsin(p) => Equation::Arcsin(Box::new(p.constant_part(variables))),
-            Equation::Arccos(p) => Equation::Arccos(Box::new(p.constant_part(variables))),
-            Equation::Arctan(p) => Equation::Arctan(Box::new(p.constant_part(variables))),
-            Equation::Equals(_) => panic!("Cannot get constant part of equals"),
             Equation::Derivative(_) => panic!("Cannot get constant part of derivative"),
+            equation => equation.map_children(|child| child.constant_part(variables)),
         }
     }
 }
@@ -133,6 +103,7 @@ impl LinearEquationSystem {
         }
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn solve(self) -> HashMap<Variable, Equation> {
         let mut a = self.augmented_matrix;
 
@@ -247,7 +218,7 @@ mod tests {
             vec![equation_1, equation_2, equation_3],
             variables.clone(),
         );
-        println!("{:?}", system);
+        println!("{system:?}");
         let solution = system.solve();
         assert!(approx_equal(
             solution.get(&x).unwrap().clone(),
@@ -269,7 +240,7 @@ mod tests {
             vec![equation_1, equation_2, equation_3],
             variables.clone(),
         );
-        println!("{:?}", system);
+        println!("{system:?}");
         let solution = system.solve();
         //TODO correcte oplossing
         assert!(approx_equal(
@@ -14,7 +14,7 @@ impl Equation {
         }
 
         match self {
-            Equation::Power(power) => power.0 == *factor,
+            Equation::Power(power) => power.0 == *factor && is_positive_integer(&power.1),
             Equation::Multiplication(multiplication) => {
                 multiplication.iter().any(|x| x.clone().has_factor(factor))
             }
@@ -26,13 +26,7 @@ impl Equation {
 
     pub fn gcd(self: &Equation) -> i64 {
         match self {
-            Equation::Variable(Variable::Integer(n)) => {
-                if *n != 0 {
-                    *n
-                } else {
-                    1
-                }
-            }
+            Equation::Variable(Variable::Integer(n)) if *n != 0 => *n,
             Equation::Addition(addition) => {
                 let mut gcd = 1;
                 let mut first_done = false;
@@ -47,7 +41,7 @@ impl Equation {
                 gcd
             }
             Equation::Multiplication(multiplication) => {
-                return multiplication.iter().map(super::Equation::gcd).product()
+                multiplication.iter().map(super::Equation::gcd).product()
             }
             Equation::Division(division) => division.0.gcd().gcd(&division.1.gcd()),
             _ => 1,
@@ -63,7 +57,9 @@ impl Equation {
                     factors.append(&mut factor.get_all_factors());
                 }
             }
-            Equation::Power(power) => factors.push(power.0.clone()),
+            Equation::Power(power) if is_positive_integer(&power.1) => {
+                factors.push(power.0.clone());
+            }
             _ => {}
         }
         factors
@@ -154,3 +150,10 @@ impl Equation {
         self
     }
 }
+
+/// Only a positive integer power of `x` has `x` as a factor, `e^{x}` doesn't have `e`
+fn is_positive_integer(exponent: &Equation) -> bool {
+    exponent
+        .get_integer_or_none()
+        .is_some_and(|exponent| exponent > 0)
+}
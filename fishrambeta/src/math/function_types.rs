@@ -3,37 +3,20 @@ use super::Variable;
 
 impl Equation {
     pub fn is_constant(&self, variable: &Variable) -> bool {
-        match self {
-            Equation::Addition(a) => a.iter().all(|x| x.is_constant(variable)),
-            Equation::Multiplication(m) => m.iter().all(|x| x.is_constant(variable)),
-            Equation::Negative(n) => n.is_constant(variable),
-            Equation::Division(d) => d.0.is_constant(variable) && d.1.is_constant(variable),
-            Equation::Power(p) => p.0.is_constant(variable) && p.1.is_constant(variable),
-            Equation::Sin(t) => t.is_constant(variable),
-            Equation::Cos(t) => t.is_constant(variable),
-            Equation::Ln(t) => t.is_constant(variable),
-            Equation::Equals(_) => panic!("Equation containing = cannot be integrated"),
-            Equation::Variable(v) => v != variable,
-            Equation::Abs(a) => a.is_constant(variable),
-            Equation::Derivative(_) => {
-                panic!("Derivative cannot be integrated")
-            }
-            Equation::Arcsin(t) => t.is_constant(variable),
-            Equation::Arccos(t) => t.is_constant(variable),
-            Equation::Arctan(t) => t.is_constant(variable),
-        }
+        self.iter_subexpressions()
+            .all(|subexpression| !matches!(subexpression, Equation::Variable(v) if v == variable))
     }
 
     pub fn is_polynomial(&self, variable: &Variable) -> bool {
         match self {
-            Equation::Addition(a) => return a.iter().all(|x| x.is_polynomial(variable)),
+            Equation::Addition(a) => a.iter().all(|x| x.is_polynomial(variable)),
             Equation::Power(p) => {
                 (p.0 == Equation::Variable(variable.clone()))
                     && (p.1.get_integer_or_none().is_some())
             }
             Equation::Negative(n) => n.is_polynomial(variable),
             Equation::Variable(_) => true,
-            Equation::Multiplication(m) => return m.iter().all(|x| x.is_polynomial(variable)),
+            Equation::Multiplication(m) => m.iter().all(|x| x.is_polynomial(variable)),
             _ => self.is_constant(variable),
         }
     }
@@ -51,16 +34,12 @@ impl Equation {
         }
 
         match self {
-            Equation::Addition(a) => {
-                return a
-                    .iter()
-                    .all(|x| x.term_is_rational_function(variable, is_in_division))
-            }
-            Equation::Multiplication(m) => {
-                return m
-                    .iter()
-                    .all(|x| x.term_is_rational_function(variable, is_in_division))
-            }
+            Equation::Addition(a) => a
+                .iter()
+                .all(|x| x.term_is_rational_function(variable, is_in_division)),
+            Equation::Multiplication(m) => m
+                .iter()
+                .all(|x| x.term_is_rational_function(variable, is_in_division)),
             Equation::Division(d) => {
                 d.0.term_is_rational_function(variable, true)
                     && d.1.term_is_rational_function(variable, true)
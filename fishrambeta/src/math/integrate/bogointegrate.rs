@@ -17,10 +17,11 @@ impl Iterator for AllPrimitives {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.index += 1;
-        let equation = random_equation(&vec!["x".to_string()], &mut self.rng, 0);
-        if self.index % 10000 != 0 {
-            println!("{}: Guessing equation: {}", self.index, equation);
-        }
+        let equation = random_equation(
+            &vec![Equation::Variable(self.integrate_to.clone()).to_latex()],
+            &mut self.rng,
+            0,
+        );
         Some(equation)
     }
 }
@@ -77,11 +78,9 @@ fn random_equation(
             random_equation(relevant_variables, rng, complexity + 2),
         ))),
         _ => match rng.gen_range(1..3) {
-            1 => {
-                return Equation::Variable(Variable::Letter(
-                    relevant_variables.choose(rng).unwrap().clone(),
-                ))
-            }
+            1 => Equation::Variable(Variable::Letter(
+                relevant_variables.choose(rng).unwrap().clone(),
+            )),
             2 => Equation::Variable(Variable::Integer(rng.gen_range(1..10))),
             3 => Equation::Variable(Variable::Rational(Rational64::from((
                 rng.gen_range(-10..10),
@@ -102,13 +101,8 @@ impl Equation {
     }
 
     fn is_primitive(&self, simplified: &Equation, integrate_to: &Variable) -> bool {
-        let is_primitive = self
-            .differentiate(integrate_to, &mut None)
+        self.differentiate(integrate_to, &mut None)
             .simplify_until_complete(&mut None)
-            == *simplified;
-        if is_primitive {
-            println!("{self} is a primitive of {simplified}");
-        }
-        is_primitive
+            == *simplified
     }
 }
@@ -1,20 +1,37 @@
 use super::{
     steps::{helpers::{cancel_step, close_step, open_step, set_step_message}, StepLogger},
-    Equation, Variable,
+    Assumptions, Constraint, Equation, Pattern, Variable,
 };
 use num_rational::Rational64;
 
 mod bogointegrate;
 mod rational;
 
+/// The largest power of a sum that is multiplied out before integrating, as larger ones take
+/// long to expand and powers of linear sums are integrated by substitution instead
+const MAX_EXPANDED_POWER: i64 = 8;
+
 impl Equation {
     pub fn integrate(
         &self,
         integrate_to: &Variable,
         step_logger: &mut Option<StepLogger>,
+    ) -> Equation {
+        self.integrate_with_assumptions(integrate_to, &Assumptions::new(), step_logger)
+    }
+
+    /// Integrates and simplifies under the assumptions, so the integral of `\frac{1}{x}` is
+    /// `\ln(x)` when `x > 0` is assumed and `\ln(|x|)` otherwise
+    pub fn integrate_with_assumptions(
+        &self,
+        integrate_to: &Variable,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
     ) -> Equation {
         open_step(step_logger, self, Some("Integrate"));
-        let mut equation_to_integrate: Equation = (*self).clone().simplify(step_logger);
+        let mut equation_to_integrate: Equation = (*self)
+            .clone()
+            .simplify_with_assumptions(assumptions, step_logger);
         let fixed_terms = equation_to_integrate.get_factors();
         let mut integrated_equation = Vec::new();
 
@@ -23,24 +40,37 @@ impl Equation {
             {
                 equation_to_integrate = equation_to_integrate
                     .remove_factor(&fixed_term)
-                    .simplify(step_logger);
+                    .simplify_with_assumptions(assumptions, step_logger);
                 integrated_equation.push(fixed_term);
             }
         }
 
+        // Multiplied out, products and small powers of sums become sums of standard integrals
+        if matches!(
+            equation_to_integrate,
+            Equation::Multiplication(_) | Equation::Power(_)
+        ) && !has_large_power_of_sum(&equation_to_integrate)
+        {
+            equation_to_integrate = equation_to_integrate.expand(step_logger);
+        }
+
         #[allow(clippy::never_loop)]
         loop {
-            println!("Equation to integrate: {equation_to_integrate}");
             if let Some(integrated_term) =
-                equation_to_integrate.standard_integrals(integrate_to, step_logger)
+                equation_to_integrate.standard_integrals(integrate_to, assumptions, step_logger)
             {
                 integrated_equation.push(integrated_term);
                 break;
             }
 
             if equation_to_integrate.is_rational(integrate_to) {
-                equation_to_integrate.integrate_rational(integrate_to, step_logger);
-                break;
+                if let Some(integrated_term) = equation_to_integrate
+                    .clone()
+                    .integrate_rational(integrate_to, step_logger)
+                {
+                    integrated_equation.push(integrated_term);
+                    break;
+                }
             }
 
             integrated_equation.push(equation_to_integrate.bogointegrate(integrate_to));
@@ -54,6 +84,7 @@ impl Equation {
     fn standard_integrals(
         &self,
         integrate_to: &Variable,
+        assumptions: &Assumptions,
         step_logger: &mut Option<StepLogger>,
     ) -> Option<Equation> {
         open_step(step_logger, self, Some("Apply standard integral"));
@@ -63,51 +94,44 @@ impl Equation {
                 Some(Equation::Addition(
                     addition
                         .iter()
-                        .map(|x| x.integrate(integrate_to, step_logger))
+                        .map(|x| {
+                            x.integrate_with_assumptions(integrate_to, assumptions, step_logger)
+                        })
                         .collect(),
                 ))
             }
-            Equation::Variable(Variable::Integer(i)) => Some(Equation::Multiplication(vec![
-                Equation::Variable(Variable::Integer(*i)),
-                Equation::Variable(integrate_to.clone()),
-            ])),
-            Equation::Variable(Variable::Rational(r)) => Some(Equation::Multiplication(vec![
-                Equation::Variable(Variable::Rational(*r)),
-                Equation::Variable(integrate_to.clone()),
-            ])),
-            Equation::Variable(v) if v == integrate_to => Some(Equation::Multiplication(vec![
-                Equation::Variable(Variable::Rational(Rational64::new(1, 2))),
-                Equation::Power(Box::new((
-                    Equation::Variable(integrate_to.clone()),
-                    Equation::Variable(Variable::Integer(2)),
-                ))),
-            ])),
-            Equation::Power(ref power) if power.0 == Equation::Variable(integrate_to.clone()) => {
-                Some(Equation::Multiplication(vec![
-                    Equation::Division(Box::new((
-                        Equation::Variable(Variable::Integer(1)),
-                        Equation::Addition(vec![
-                            power.1.clone(),
-                            Equation::Variable(Variable::Integer(1)),
-                        ]),
-                    ))),
-                    Equation::Power(Box::new((
-                        Equation::Variable(integrate_to.clone()),
-                        Equation::Addition(vec![
-                            power.1.clone(),
-                            Equation::Variable(Variable::Integer(1)),
-                        ]),
-                    ))),
-                ]))
-            }
-            Equation::Sin(ref x) if **x == Equation::Variable(integrate_to.clone()) => {
-                Some(Equation::Negative(Box::new(Equation::Cos(Box::new(
-                    Equation::Variable(integrate_to.clone()),
-                )))))
+            Equation::Variable(number @ (Variable::Integer(_) | Variable::Rational(_))) => {
+                Some(Equation::from(number.clone()) * integrate_to.clone())
             }
-            Equation::Cos(ref x) if **x == Equation::Variable(integrate_to.clone()) => Some(
-                Equation::Sin(Box::new(Equation::Variable(integrate_to.clone()))),
+            Equation::Variable(v) if v == integrate_to => Some(
+                Equation::from(Rational64::new(1, 2)) * Equation::from(v.clone()).pow(2),
             ),
+            // Substitutes the linear base, so `(2\cdot x+1)^{n}` isn't multiplied out
+            Equation::Power(_) => {
+                let pattern = Pattern::new(Equation::wildcard("u").pow(Equation::wildcard("n")))
+                    .constrain("n", Constraint::ConstantIn(integrate_to.clone()));
+                self.matches(&pattern).and_then(|bindings| {
+                    let base = bindings.get("u")?.clone();
+                    let slope = linear_argument(&base, integrate_to)?;
+                    let exponent = bindings.apply(&(Equation::wildcard("n") + 1));
+                    if exponent.calculate_exact() == Some(0.into()) {
+                        return Some(divided_by(ln_abs(base, assumptions), slope));
+                    }
+                    Some(divided_by(base.pow(exponent.clone()) / exponent, slope))
+                })
+            }
+            Equation::Division(division) if division.0.is_constant(integrate_to) => {
+                linear_argument(&division.1, integrate_to).map(|slope| {
+                    divided_by(
+                        division.0.clone() * ln_abs(division.1.clone(), assumptions),
+                        slope,
+                    )
+                })
+            }
+            Equation::Sin(ref argument) => linear_argument(argument, integrate_to)
+                .map(|slope| divided_by(-(**argument).clone().cos(), slope)),
+            Equation::Cos(ref argument) => linear_argument(argument, integrate_to)
+                .map(|slope| divided_by((**argument).clone().sin(), slope)),
             Equation::Arcsin(ref _x) => todo!(),
             Equation::Arccos(ref _x) => todo!(),
             Equation::Arctan(ref _x) => todo!(),
@@ -120,3 +144,47 @@ impl Equation {
         result
     }
 }
+
+/// The slope `a` if the argument is `a*x+b` for constants `a` and `b`
+fn linear_argument(argument: &Equation, integrate_to: &Variable) -> Option<Equation> {
+    let x = Equation::Variable(integrate_to.clone());
+    let pattern = Pattern::new(Equation::wildcard("a") * x + Equation::wildcard("b"))
+        .constrain("a", Constraint::ConstantIn(integrate_to.clone()))
+        .constrain("b", Constraint::ConstantIn(integrate_to.clone()))
+        .constrain("a", Constraint::Optional)
+        .constrain("b", Constraint::Optional);
+    argument
+        .matches(&pattern)
+        .and_then(|bindings| bindings.get("a").cloned())
+}
+
+/// `\ln(|x|)`, without the absolute value when `x` is assumed to be positive
+fn ln_abs(argument: Equation, assumptions: &Assumptions) -> Equation {
+    if assumptions.is_positive(&argument) {
+        argument.ln()
+    } else {
+        argument.abs().ln()
+    }
+}
+
+/// Whether a sum is raised to a power above [`MAX_EXPANDED_POWER`] somewhere in the equation
+fn has_large_power_of_sum(equation: &Equation) -> bool {
+    equation.iter_subexpressions().any(|subexpression| match subexpression {
+        Equation::Power(power) => {
+            matches!(power.0, Equation::Addition(_))
+                && power
+                    .1
+                    .get_integer_or_none()
+                    .is_some_and(|exponent| exponent > MAX_EXPANDED_POWER)
+        }
+        _ => false,
+    })
+}
+
+fn divided_by(equation: Equation, divisor: Equation) -> Equation {
+    if divisor == Equation::from(1) {
+        equation
+    } else {
+        equation / divisor
+    }
+}
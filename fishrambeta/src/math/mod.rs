@@ -1,7 +1,9 @@
 use num_rational::Rational64;
 
+mod assumptions;
 mod calculate;
 mod compare;
+pub mod cost;
 mod differentiate;
 mod equation_system;
 mod error_analysis;
@@ -9,13 +11,35 @@ mod factors;
 mod function_types;
 mod integrate;
 mod multiply_by;
+mod ops;
+mod pattern;
 mod polynomial;
+mod rules;
+mod saturate;
+#[cfg(feature = "serde")]
+mod serde_rational;
 mod simplify;
 pub mod steps;
 mod taylor_series;
+mod transform;
+mod traversal;
+
+pub use assumptions::{Assumption, AssumptionError, Assumptions};
+pub use equation_system::LinearEquationSystem;
+pub use pattern::{Bindings, Constraint, Pattern};
+pub use rules::{Rule, RuleError, RuleErrorKind, RuleSet};
+pub use saturate::SaturationLimits;
+pub use simplify::{Convergence, Simplified, SimplifyOptions};
+pub use traversal::Subexpressions;
 
 ///Represents a generic math object
+///
+///With the `serde` feature the tree serializes with every variant as an object with a single key,
+///for example `x+\frac{1}{2}` as
+///`{"Addition":[{"Variable":{"Letter":"x"}},{"Variable":{"Rational":[1,2]}}]}`. The
+///`equation.schema.json` file next to the crate manifest describes the JSON format.
 #[derive(Eq, PartialEq, Hash, Clone, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Equation {
     Variable(Variable),
     Negative(Box<Equation>),
@@ -35,8 +59,10 @@ pub enum Equation {
 }
 ///Represents a single number
 #[derive(Eq, PartialEq, Hash, Clone, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Variable {
     Integer(i64),
+    #[cfg_attr(feature = "serde", serde(with = "serde_rational"))]
     Rational(Rational64),
     Constant(Constant),
     Letter(String),
@@ -44,6 +70,7 @@ pub enum Variable {
 }
 ///Mathematical constants
 #[derive(Eq, PartialEq, Hash, Clone, Copy, Debug, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Constant {
     PI,
     E,
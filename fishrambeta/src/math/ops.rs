@@ -0,0 +1,133 @@
+use crate::math::{Constant, Equation, Variable};
+use num_rational::Rational64;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Parses LaTeX into an [`Equation`](crate::math::Equation) when the expression is evaluated,
+/// as a shorthand for [`Equation::from_latex`](crate::math::Equation::from_latex) without
+/// implicit multiplication. Nothing is checked at compile time, so malformed LaTeX compiles
+/// and only panics when it is parsed.
+///
+/// ```
+/// use fishrambeta::math::Equation;
+/// use fishrambeta::parse_latex;
+///
+/// assert_eq!(parse_latex!("x^{2}+1"), Equation::var("x").pow(2) + 1);
+/// ```
+///
+/// # Panics
+/// Panics when the LaTeX is malformed
+#[macro_export]
+macro_rules! parse_latex {
+    ($latex:literal) => {
+        $crate::math::Equation::from_latex($latex, false)
+    };
+}
+
+impl From<i64> for Equation {
+    fn from(integer: i64) -> Self {
+        Equation::Variable(Variable::Integer(integer))
+    }
+}
+
+impl From<Rational64> for Equation {
+    fn from(rational: Rational64) -> Self {
+        Equation::Variable(Variable::Rational(rational))
+    }
+}
+
+impl From<Constant> for Equation {
+    fn from(constant: Constant) -> Self {
+        Equation::Variable(Variable::Constant(constant))
+    }
+}
+
+impl From<Variable> for Equation {
+    fn from(variable: Variable) -> Self {
+        Equation::Variable(variable)
+    }
+}
+
+impl Equation {
+    /// A variable with the given name, like `x` or `m_e`
+    pub fn var(name: &str) -> Equation {
+        Equation::Variable(Variable::Letter(name.to_string()))
+    }
+
+    pub fn pow(self, exponent: impl Into<Equation>) -> Equation {
+        Equation::Power(Box::new((self, exponent.into())))
+    }
+
+    pub fn sqrt(self) -> Equation {
+        self.pow(Rational64::new(1, 2))
+    }
+
+    pub fn ln(self) -> Equation {
+        Equation::Ln(Box::new(self))
+    }
+
+    pub fn sin(self) -> Equation {
+        Equation::Sin(Box::new(self))
+    }
+
+    pub fn cos(self) -> Equation {
+        Equation::Cos(Box::new(self))
+    }
+
+    pub fn abs(self) -> Equation {
+        Equation::Abs(Box::new(self))
+    }
+}
+
+/// Adds to an existing sum instead of nesting it, so `a + b + c` has three terms
+impl<T: Into<Equation>> Add<T> for Equation {
+    type Output = Equation;
+
+    fn add(self, other: T) -> Equation {
+        match self {
+            Equation::Addition(mut terms) => {
+                terms.push(other.into());
+                Equation::Addition(terms)
+            }
+            term => Equation::Addition(vec![term, other.into()]),
+        }
+    }
+}
+
+impl<T: Into<Equation>> Sub<T> for Equation {
+    type Output = Equation;
+
+    fn sub(self, other: T) -> Equation {
+        self + -other.into()
+    }
+}
+
+/// Multiplies into an existing product instead of nesting it, so `a * b * c` has three factors
+impl<T: Into<Equation>> Mul<T> for Equation {
+    type Output = Equation;
+
+    fn mul(self, other: T) -> Equation {
+        match self {
+            Equation::Multiplication(mut factors) => {
+                factors.push(other.into());
+                Equation::Multiplication(factors)
+            }
+            factor => Equation::Multiplication(vec![factor, other.into()]),
+        }
+    }
+}
+
+impl<T: Into<Equation>> Div<T> for Equation {
+    type Output = Equation;
+
+    fn div(self, other: T) -> Equation {
+        Equation::Division(Box::new((self, other.into())))
+    }
+}
+
+impl Neg for Equation {
+    type Output = Equation;
+
+    fn neg(self) -> Equation {
+        Equation::Negative(Box::new(self))
+    }
+}
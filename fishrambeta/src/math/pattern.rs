@@ -0,0 +1,297 @@
+use crate::math::{Equation, Variable};
+use std::collections::{BTreeMap, HashMap};
+use std::mem::discriminant;
+
+/// A condition on what a wildcard of a [`Pattern`] may match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    /// The matched expression doesn't depend on the variable
+    ConstantIn(Variable),
+    /// The matched expression is an exact integer
+    Integer,
+    /// The matched expression is an exact integer or rational
+    Number,
+    /// A term of a sum or factor of a product that may be missing, matching 0 or 1, so
+    /// `?a\cdot x+?b` matches `x` when both are optional
+    Optional,
+}
+
+/// An equation in which letters starting with `?`, like `?a` in `?a\cdot \sin(?b)`, are wildcards
+/// that match any subexpression. A wildcard that occurs more than once has to match the same
+/// subexpression every time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    equation: Equation,
+    constraints: Vec<(String, Constraint)>,
+}
+
+/// The subexpressions the wildcards of a [`Pattern`] matched, by name without the `?`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bindings(BTreeMap<String, Equation>);
+
+#[derive(Clone, Copy)]
+enum Operator {
+    Addition,
+    Multiplication,
+}
+
+impl Pattern {
+    pub fn new(equation: Equation) -> Self {
+        Pattern {
+            equation,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Parses a pattern written in LaTeX, like `?a\cdot \sin(?b)`
+    ///
+    /// # Panics
+    /// Panics when the LaTeX is malformed
+    pub fn from_latex(latex: &str) -> Self {
+        Pattern::new(Equation::from_latex(latex, false))
+    }
+
+    /// Only lets the wildcard `?name` match expressions that satisfy the constraint
+    pub fn constrain(mut self, name: &str, constraint: Constraint) -> Self {
+        self.constraints.push((name.to_string(), constraint));
+        self
+    }
+
+    pub fn equation(&self) -> &Equation {
+        &self.equation
+    }
+
+    /// The same constraints on another equation
+    pub(super) fn with_equation(&self, equation: Equation) -> Pattern {
+        Pattern {
+            equation,
+            constraints: self.constraints.clone(),
+        }
+    }
+
+    /// All ways `subject` matches `pattern`, each extending `bindings`
+    fn match_all(
+        &self,
+        pattern: &Equation,
+        subject: &Equation,
+        bindings: Bindings,
+    ) -> Vec<Bindings> {
+        if let Some(name) = wildcard_name(pattern) {
+            return self.bind(name, subject, bindings).into_iter().collect();
+        }
+        match (pattern, subject) {
+            (Equation::Addition(patterns), Equation::Addition(subjects))
+            | (Equation::Multiplication(patterns), Equation::Multiplication(subjects)) => {
+                let operator = Operator::of(pattern);
+                self.match_unordered(patterns, subjects.iter().collect(), operator, bindings)
+            }
+            // A sum or product pattern matches a single term when the other terms are optional
+            // wildcards
+            (Equation::Addition(patterns) | Equation::Multiplication(patterns), _) => {
+                let operator = Operator::of(pattern);
+                self.match_unordered(patterns, vec![subject], operator, bindings)
+            }
+            (Equation::Variable(_), _) => {
+                if pattern == subject {
+                    vec![bindings]
+                } else {
+                    vec![]
+                }
+            }
+            (Equation::Derivative((_, pattern_partial)), Equation::Derivative((_, partial)))
+                if pattern_partial != partial =>
+            {
+                vec![]
+            }
+            _ if discriminant(pattern) == discriminant(subject) => pattern
+                .children()
+                .into_iter()
+                .zip(subject.children())
+                .fold(vec![bindings], |all_bindings, (pattern, subject)| {
+                    all_bindings
+                        .into_iter()
+                        .flat_map(|bindings| self.match_all(pattern, subject, bindings))
+                        .collect()
+                }),
+            _ => vec![],
+        }
+    }
+
+    /// Matches the terms of a sum or the factors of a product in any order. A wildcard that is a
+    /// term by itself takes the terms no other pattern matched, and only takes none of them when
+    /// it is optional.
+    fn match_unordered(
+        &self,
+        patterns: &[Equation],
+        subjects: Vec<&Equation>,
+        operator: Operator,
+        bindings: Bindings,
+    ) -> Vec<Bindings> {
+        let (wildcards, others): (Vec<_>, Vec<_>) = patterns
+            .iter()
+            .partition(|pattern| wildcard_name(pattern).is_some());
+        let wildcards = wildcards
+            .into_iter()
+            .filter_map(wildcard_name)
+            .collect::<Vec<_>>();
+        self.match_remaining(&others, &wildcards, subjects, operator, bindings)
+    }
+
+    fn match_remaining(
+        &self,
+        patterns: &[&Equation],
+        wildcards: &[&str],
+        subjects: Vec<&Equation>,
+        operator: Operator,
+        bindings: Bindings,
+    ) -> Vec<Bindings> {
+        if let Some((pattern, patterns)) = patterns.split_first() {
+            let mut all_bindings = Vec::new();
+            for (i, subject) in subjects.iter().enumerate() {
+                for bindings in self.match_all(pattern, subject, bindings.clone()) {
+                    let mut remaining = subjects.clone();
+                    remaining.remove(i);
+                    all_bindings.extend(
+                        self.match_remaining(patterns, wildcards, remaining, operator, bindings),
+                    );
+                }
+            }
+            return all_bindings;
+        }
+        match wildcards {
+            [] if subjects.is_empty() => vec![bindings],
+            [] => vec![],
+            [wildcard] if subjects.is_empty() && !self.is_optional(wildcard) => vec![],
+            [wildcard] => self
+                .bind(wildcard, &operator.combine(subjects), bindings)
+                .into_iter()
+                .collect(),
+            [wildcard, wildcards @ ..] => {
+                let mut all_bindings = Vec::new();
+                if self.is_optional(wildcard) {
+                    let identity = operator.combine(Vec::new());
+                    if let Some(bindings) = self.bind(wildcard, &identity, bindings.clone()) {
+                        all_bindings.extend(self.match_remaining(
+                            &[],
+                            wildcards,
+                            subjects.clone(),
+                            operator,
+                            bindings,
+                        ));
+                    }
+                }
+                for (i, subject) in subjects.iter().enumerate() {
+                    if let Some(bindings) = self.bind(wildcard, subject, bindings.clone()) {
+                        let mut remaining = subjects.clone();
+                        remaining.remove(i);
+                        all_bindings.extend(self.match_remaining(
+                            &[],
+                            wildcards,
+                            remaining,
+                            operator,
+                            bindings,
+                        ));
+                    }
+                }
+                all_bindings
+            }
+        }
+    }
+
+    fn is_optional(&self, name: &str) -> bool {
+        self.constraints.iter().any(|(constrained, constraint)| {
+            constrained == name && *constraint == Constraint::Optional
+        })
+    }
+
+    fn bind(&self, name: &str, subject: &Equation, mut bindings: Bindings) -> Option<Bindings> {
+        if let Some(bound) = bindings.0.get(name) {
+            return (bound == subject).then_some(bindings);
+        }
+        let satisfied = self
+            .constraints
+            .iter()
+            .filter(|(constrained, _)| constrained == name)
+            .all(|(_, constraint)| constraint.is_satisfied_by(subject));
+        if !satisfied {
+            return None;
+        }
+        bindings.0.insert(name.to_string(), subject.clone());
+        Some(bindings)
+    }
+}
+
+impl Constraint {
+    fn is_satisfied_by(&self, equation: &Equation) -> bool {
+        match self {
+            Constraint::ConstantIn(variable) => equation.is_constant(variable),
+            Constraint::Integer => equation
+                .calculate_exact()
+                .is_some_and(|number| number.is_integer()),
+            Constraint::Number => equation.calculate_exact().is_some(),
+            Constraint::Optional => true,
+        }
+    }
+}
+
+impl Operator {
+    fn of(equation: &Equation) -> Operator {
+        match equation {
+            Equation::Addition(_) => Operator::Addition,
+            _ => Operator::Multiplication,
+        }
+    }
+
+    /// The sum or product of the terms, which is the identity for no terms
+    fn combine(self, terms: Vec<&Equation>) -> Equation {
+        match (self, terms.as_slice()) {
+            (Operator::Addition, []) => Equation::from(0),
+            (Operator::Multiplication, []) => Equation::from(1),
+            (_, [term]) => (*term).clone(),
+            (Operator::Addition, _) => Equation::Addition(terms.into_iter().cloned().collect()),
+            (Operator::Multiplication, _) => {
+                Equation::Multiplication(terms.into_iter().cloned().collect())
+            }
+        }
+    }
+}
+
+fn wildcard_name(equation: &Equation) -> Option<&str> {
+    match equation {
+        Equation::Variable(Variable::Letter(name)) => name.strip_prefix('?'),
+        _ => None,
+    }
+}
+
+impl Bindings {
+    /// The subexpression the wildcard `?name` matched
+    pub fn get(&self, name: &str) -> Option<&Equation> {
+        self.0.get(name)
+    }
+
+    /// Replaces the wildcards in `template` with what they matched
+    pub fn apply(&self, template: &Equation) -> Equation {
+        template.substitute(
+            &self
+                .0
+                .iter()
+                .map(|(name, equation)| (Variable::Letter(format!("?{name}")), equation.clone()))
+                .collect::<HashMap<_, _>>(),
+        )
+    }
+}
+
+impl Equation {
+    /// The wildcard `?name` for building a [`Pattern`] in code
+    pub fn wildcard(name: &str) -> Equation {
+        Equation::Variable(Variable::Letter(format!("?{name}")))
+    }
+
+    /// Matches the equation against the pattern, treating sums and products as unordered
+    pub fn matches(&self, pattern: &Pattern) -> Option<Bindings> {
+        pattern
+            .match_all(&pattern.equation, self, Bindings::default())
+            .into_iter()
+            .next()
+    }
+}
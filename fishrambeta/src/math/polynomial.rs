@@ -74,10 +74,9 @@ impl Polynomial {
     }
 
     pub fn is_zero(&self) -> bool {
-        return self
-            .terms
+        self.terms
             .iter()
-            .all(|x| x.clone().simplify(&mut None) == Equation::Variable(Variable::Integer(0)));
+            .all(|x| x.clone().simplify(&mut None) == Equation::Variable(Variable::Integer(0)))
     }
 
     pub fn is_one(&self) -> bool {
@@ -139,7 +138,10 @@ impl Polynomial {
 
         let mut remainder = self.simplify();
         let divisor = other.simplify();
-        let mut quotient = Polynomial::zero(base.clone(), remainder.degree() - divisor.degree());
+        let mut quotient = Polynomial::zero(
+            base.clone(),
+            remainder.degree().saturating_sub(divisor.degree()),
+        );
 
         while remainder.degree() >= divisor.degree() && !remainder.is_zero() {
             let remainder_degree = remainder.degree();
@@ -169,13 +171,14 @@ impl Polynomial {
             return gcd;
         }
 
-        let (q, r) = a.div(b.clone());
+        let (_, r) = a.div(b.clone());
         let r = r.simplify().simplify();
         b.gcd(r)
     }
 
     /// Compute the square free factorization of a polynomial, algorithm 8.2 from algorithms for
     /// computer algebra
+    #[allow(clippy::many_single_char_names)]
     pub fn square_free_factorization(self) -> Vec<Polynomial> {
         let mut factors: Vec<_> = vec![];
         let a = self.clone().simplify();
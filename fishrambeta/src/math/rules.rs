@@ -0,0 +1,273 @@
+use crate::math::cost::node_count;
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::{Constraint, Equation, Pattern, Variable};
+use crate::parser::ParseError;
+use std::collections::HashSet;
+use std::fmt;
+
+/// How often the rules are applied to the whole equation before giving up on a fixed point, as
+/// rules like commutativity never reach one
+const MAX_PASSES: usize = 100;
+
+/// How many passes in a row may leave the equation no smaller than the smallest form seen, so
+/// rules that keep growing it stop early while expanding rules like distributivity still finish
+const MAX_PASSES_WITHOUT_SHRINKING: usize = 8;
+
+/// The hidden wildcard that takes the other terms when a rule for part of a sum or product is
+/// applied to a longer one
+const REST: &str = "";
+
+/// A rewrite rule `lhs -> rhs`, which replaces subexpressions matching the left-hand side
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    name: String,
+    pattern: Pattern,
+    replacement: Equation,
+}
+
+/// Rules that are tried in order, see [`Equation::rewrite`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+/// An error in a rule file, with the line it is on counting from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    pub kind: RuleErrorKind,
+    pub line: usize,
+}
+
+/// The different ways a rule can be malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleErrorKind {
+    MissingName,
+    MissingArrow,
+    InvalidCondition(String),
+    InvalidEquation(ParseError),
+}
+
+impl Rule {
+    /// A rule replacing what `pattern` matches with `replacement`, in which the wildcards of the
+    /// pattern are filled in. The name is the message of the step in the [`StepLogger`].
+    pub fn new(name: &str, pattern: Pattern, replacement: Equation) -> Self {
+        Rule {
+            name: name.to_string(),
+            pattern,
+            replacement,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Applies the rule to the equation itself, not its subexpressions. A rule for a sum or
+    /// product also applies to the matching terms of a longer sum or product.
+    pub fn apply(&self, equation: &Equation) -> Option<Equation> {
+        if let Some(bindings) = equation.matches(&self.pattern) {
+            return Some(bindings.apply(&self.replacement));
+        }
+        let with_rest = match self.pattern.equation() {
+            Equation::Addition(terms) if terms.len() < equation_len(equation) => {
+                let mut terms = terms.clone();
+                terms.push(Equation::wildcard(REST));
+                Equation::Addition(terms)
+            }
+            Equation::Multiplication(factors) if factors.len() < equation_len(equation) => {
+                let mut factors = factors.clone();
+                factors.push(Equation::wildcard(REST));
+                Equation::Multiplication(factors)
+            }
+            _ => return None,
+        };
+        let bindings = equation.matches(&self.pattern.with_equation(with_rest))?;
+        let replacement = bindings.apply(&self.replacement);
+        Some(match (equation, bindings.get(REST)?.clone()) {
+            (Equation::Addition(_), Equation::Addition(rest)) => {
+                Equation::Addition([vec![replacement], rest].concat())
+            }
+            (Equation::Addition(_), rest) => Equation::Addition(vec![replacement, rest]),
+            (_, Equation::Multiplication(rest)) => {
+                Equation::Multiplication([vec![replacement], rest].concat())
+            }
+            (_, rest) => Equation::Multiplication(vec![replacement, rest]),
+        })
+    }
+}
+
+fn equation_len(equation: &Equation) -> usize {
+    match equation {
+        Equation::Addition(terms) | Equation::Multiplication(terms) => terms.len(),
+        _ => 1,
+    }
+}
+
+impl RuleSet {
+    pub fn new() -> Self {
+        RuleSet::default()
+    }
+
+    pub fn push(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Parses rules written one per line as `name: lhs -> rhs`, optionally followed by
+    /// conditions on the wildcards like `if ?n integer, ?a constant in x`. The conditions are
+    /// `integer`, `number`, `optional` and `constant in` a variable. Empty lines and lines
+    /// starting with `#` are skipped.
+    ///
+    /// ```text
+    /// # Optics
+    /// magnification: \frac{?v}{?u} -> m
+    /// power of a power: (?a^{?m})^{?n} -> ?a^{?m\cdot ?n} if ?n integer
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`RuleError`] for the first line that isn't a valid rule
+    pub fn parse(text: &str) -> Result<RuleSet, RuleError> {
+        let mut rules = RuleSet::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let rule = parse_rule(line).map_err(|kind| RuleError {
+                kind,
+                line: index + 1,
+            })?;
+            rules.push(rule);
+        }
+        Ok(rules)
+    }
+}
+
+fn parse_rule(line: &str) -> Result<Rule, RuleErrorKind> {
+    let (name, rule) = line.split_once(':').ok_or(RuleErrorKind::MissingName)?;
+    let (lhs, rhs) = rule.split_once("->").ok_or(RuleErrorKind::MissingArrow)?;
+    let (rhs, conditions) = match rhs.split_once(" if ") {
+        Some((rhs, conditions)) => (rhs, Some(conditions)),
+        None => (rhs, None),
+    };
+    let parse = |latex: &str| {
+        Equation::try_from_latex(latex.trim(), false).map_err(RuleErrorKind::InvalidEquation)
+    };
+    let mut pattern = Pattern::new(parse(lhs)?);
+    for condition in conditions
+        .into_iter()
+        .flat_map(|conditions| conditions.split(','))
+    {
+        let (name, constraint) = parse_condition(condition.trim())
+            .ok_or_else(|| RuleErrorKind::InvalidCondition(condition.trim().to_string()))?;
+        pattern = pattern.constrain(name, constraint);
+    }
+    Ok(Rule::new(name.trim(), pattern, parse(rhs)?))
+}
+
+fn parse_condition(condition: &str) -> Option<(&str, Constraint)> {
+    let (wildcard, constraint) = condition.strip_prefix('?')?.split_once(' ')?;
+    let constraint = match constraint.trim() {
+        "integer" => Constraint::Integer,
+        "number" => Constraint::Number,
+        "optional" => Constraint::Optional,
+        constraint => {
+            match Equation::try_from_latex(constraint.strip_prefix("constant in")?, false) {
+                Ok(Equation::Variable(variable @ (Variable::Letter(_) | Variable::Vector(_)))) => {
+                    Constraint::ConstantIn(variable)
+                }
+                _ => return None,
+            }
+        }
+    };
+    Some((wildcard, constraint))
+}
+
+impl Equation {
+    /// Applies the first matching rule to every subexpression, children before their parents,
+    /// until no rule applies anymore. Every application is a step named after the rule. Rewriting
+    /// also stops when a pass returns to an earlier form, as with commutativity, or when the
+    /// equation hasn't shrunk for a while.
+    pub fn rewrite(self, rules: &RuleSet, step_logger: &mut Option<StepLogger>) -> Equation {
+        let mut equation = self;
+        let mut seen = HashSet::from([equation.clone()]);
+        let mut smallest = node_count(&equation);
+        let mut passes_without_shrinking = 0;
+        for _ in 0..MAX_PASSES {
+            let rewritten = equation.clone().rewrite_once(rules, step_logger);
+            if rewritten == equation || !seen.insert(rewritten.clone()) {
+                break;
+            }
+            equation = rewritten;
+            if node_count(&equation) < smallest {
+                smallest = node_count(&equation);
+                passes_without_shrinking = 0;
+            } else {
+                passes_without_shrinking += 1;
+                if passes_without_shrinking == MAX_PASSES_WITHOUT_SHRINKING {
+                    break;
+                }
+            }
+        }
+        equation
+    }
+
+    fn rewrite_once(self, rules: &RuleSet, step_logger: &mut Option<StepLogger>) -> Equation {
+        let equation = self.map_children(|child| child.rewrite_once(rules, step_logger));
+        for rule in &rules.rules {
+            if let Some(rewritten) = rule.apply(&equation) {
+                open_step(step_logger, &equation, Some(&rule.name));
+                close_step(step_logger, &rewritten);
+                return rewritten;
+            }
+        }
+        equation
+    }
+
+    /// Simplifies the equation and applies the rules in turn until neither changes it
+    pub fn simplify_with_rules(
+        self,
+        rules: &RuleSet,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Equation {
+        let mut equation = self;
+        for _ in 0..MAX_PASSES {
+            let simplified = equation
+                .clone()
+                .simplify_until_complete(step_logger)
+                .rewrite(rules, step_logger);
+            if simplified == equation {
+                break;
+            }
+            equation = simplified;
+        }
+        equation
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on line {}", self.kind, self.line)
+    }
+}
+
+impl fmt::Display for RuleErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleErrorKind::MissingName => {
+                write!(f, "rule has no name, expected 'name: lhs -> rhs'")
+            }
+            RuleErrorKind::MissingArrow => write!(f, "rule has no '->' between its sides"),
+            RuleErrorKind::InvalidCondition(condition) => {
+                write!(f, "invalid condition '{condition}'")
+            }
+            RuleErrorKind::InvalidEquation(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
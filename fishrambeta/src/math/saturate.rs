@@ -0,0 +1,256 @@
+use crate::math::steps::StepLogger;
+use crate::math::{Equation, Variable};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The transformations whose results are added to the e-graph as equivalent forms, so the
+/// identities the simplifier and the transformations know are not repeated here
+const TRANSFORMATIONS: [fn(Equation, &mut Option<StepLogger>) -> Equation; 4] = [
+    Equation::simplify,
+    Equation::expand,
+    Equation::factor,
+    Equation::together,
+];
+
+/// When [`Equation::simplify_saturate`] stops adding forms, even if more transformations apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaturationLimits {
+    /// How often the transformations are applied to every form in the e-graph
+    pub max_iterations: usize,
+    /// How many forms of subexpressions the e-graph may hold
+    pub max_nodes: usize,
+}
+
+impl Default for SaturationLimits {
+    fn default() -> Self {
+        SaturationLimits {
+            max_iterations: 8,
+            max_nodes: 2000,
+        }
+    }
+}
+
+type ClassId = usize;
+
+/// One form of an e-class: an operator applied to e-classes. The operator is the equation with
+/// its children replaced by a placeholder, so every variant works without listing them again.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct ENode {
+    operator: Equation,
+    children: Vec<ClassId>,
+}
+
+/// E-classes of equivalent equations, where every form of a subexpression refers to the classes
+/// of its children, so a form is shared by every equation containing it
+#[derive(Default)]
+struct EGraph {
+    /// Union-find of the classes, a class is canonical when it is its own parent
+    parents: Vec<ClassId>,
+    classes: BTreeMap<ClassId, BTreeSet<ENode>>,
+    memo: HashMap<ENode, ClassId>,
+}
+
+impl ENode {
+    fn new(equation: &Equation, children: Vec<ClassId>) -> Self {
+        ENode {
+            operator: equation
+                .clone()
+                .map_children(|_| Equation::Variable(Variable::Integer(0))),
+            children,
+        }
+    }
+
+    fn to_equation(&self, children: Vec<Equation>) -> Equation {
+        let mut children = children.into_iter();
+        self.operator.clone().map_children(|_| {
+            children
+                .next()
+                .expect("An e-node has an e-class for every child")
+        })
+    }
+}
+
+impl EGraph {
+    fn find(&self, mut class: ClassId) -> ClassId {
+        while self.parents[class] != class {
+            class = self.parents[class];
+        }
+        class
+    }
+
+    fn canonicalize(&self, node: &ENode) -> ENode {
+        ENode {
+            operator: node.operator.clone(),
+            children: node
+                .children
+                .iter()
+                .map(|&child| self.find(child))
+                .collect(),
+        }
+    }
+
+    fn add(&mut self, node: &ENode) -> ClassId {
+        let node = self.canonicalize(node);
+        if let Some(&class) = self.memo.get(&node) {
+            return self.find(class);
+        }
+        let class = self.parents.len();
+        self.parents.push(class);
+        self.classes.insert(class, BTreeSet::from([node.clone()]));
+        self.memo.insert(node, class);
+        class
+    }
+
+    fn add_equation(&mut self, equation: &Equation) -> ClassId {
+        let children = equation
+            .children()
+            .into_iter()
+            .map(|child| self.add_equation(child))
+            .collect();
+        self.add(&ENode::new(equation, children))
+    }
+
+    /// Merges the classes and returns whether they were different
+    fn union(&mut self, a: ClassId, b: ClassId) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        self.parents[b] = a;
+        let nodes = self.classes.remove(&b).unwrap_or_default();
+        self.classes.entry(a).or_default().extend(nodes);
+        true
+    }
+
+    /// Merges classes with the same form until every form is in one class, as merging the
+    /// children of two forms makes the forms equal
+    fn rebuild(&mut self) {
+        loop {
+            let mut memo = HashMap::new();
+            let mut unions = Vec::new();
+            for (&class, nodes) in &self.classes {
+                for node in nodes {
+                    match memo.get(&self.canonicalize(node)) {
+                        Some(&other) if other != class => unions.push((other, class)),
+                        Some(_) => {}
+                        None => {
+                            memo.insert(self.canonicalize(node), class);
+                        }
+                    }
+                }
+            }
+            if unions.is_empty() {
+                self.classes = self
+                    .classes
+                    .iter()
+                    .map(|(&class, nodes)| {
+                        (
+                            class,
+                            nodes.iter().map(|node| self.canonicalize(node)).collect(),
+                        )
+                    })
+                    .collect();
+                self.memo = memo;
+                return;
+            }
+            for (a, b) in unions {
+                self.union(a, b);
+            }
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.classes.values().map(BTreeSet::len).sum()
+    }
+
+    /// The form of `node` built from the cheapest form of every child, if they all have one
+    fn node_equation(
+        &self,
+        node: &ENode,
+        best: &HashMap<ClassId, (usize, Equation)>,
+    ) -> Option<Equation> {
+        let children = node
+            .children
+            .iter()
+            .map(|child| best.get(&self.find(*child)).map(|(_, e)| e.clone()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(node.to_equation(children))
+    }
+
+    /// The cheapest form of every class, built bottom-up from the cheapest forms of the children
+    fn extract(
+        &self,
+        cost_fn: &impl Fn(&Equation) -> usize,
+    ) -> HashMap<ClassId, (usize, Equation)> {
+        let mut best: HashMap<ClassId, (usize, Equation)> = HashMap::new();
+        loop {
+            let mut changed = false;
+            for (&class, nodes) in &self.classes {
+                for node in nodes {
+                    let Some(equation) = self.node_equation(node, &best) else {
+                        continue;
+                    };
+                    let cost = cost_fn(&equation);
+                    if best.get(&class).is_none_or(|(best, _)| cost < *best) {
+                        best.insert(class, (cost, equation));
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                return best;
+            }
+        }
+    }
+}
+
+impl Equation {
+    /// Simplifies by equality saturation: every form the simplifier, [`Equation::expand`],
+    /// [`Equation::factor`] and [`Equation::together`] produce is kept in an e-graph, and the form
+    /// with the lowest `cost_fn` is returned. See [`crate::math::cost`] for cost functions.
+    ///
+    /// This is not full e-matching: the transformations see every form of a class with the
+    /// cheapest form of each child, not every combination of the forms of the children, so a
+    /// rewrite that needs a more expensive form of a child is missed.
+    pub fn simplify_saturate(
+        self,
+        cost_fn: impl Fn(&Equation) -> usize,
+        limits: SaturationLimits,
+    ) -> Equation {
+        let mut egraph = EGraph::default();
+        let root = egraph.add_equation(&self);
+        for _ in 0..limits.max_iterations {
+            let best = egraph.extract(&cost_fn);
+            let mut rewrites = Vec::new();
+            for (&class, nodes) in &egraph.classes {
+                for node in nodes {
+                    let Some(equation) = egraph.node_equation(node, &best) else {
+                        continue;
+                    };
+                    rewrites.extend(
+                        TRANSFORMATIONS
+                            .iter()
+                            .map(|transform| (class, transform(equation.clone(), &mut None))),
+                    );
+                }
+            }
+            let size = egraph.size();
+            let mut changed = false;
+            for (class, rewritten) in rewrites {
+                let rewritten = egraph.add_equation(&rewritten);
+                changed |= egraph.union(class, rewritten);
+                if egraph.size() >= limits.max_nodes {
+                    break;
+                }
+            }
+            egraph.rebuild();
+            if !changed && egraph.size() == size || egraph.size() >= limits.max_nodes {
+                break;
+            }
+        }
+        egraph
+            .extract(&cost_fn)
+            .remove(&egraph.find(root))
+            .expect("Every class has a form without cycles")
+            .1
+    }
+}
@@ -0,0 +1,27 @@
+//! Serializes a `Rational64` as a `[numerator, denominator]` pair
+
+use num_rational::Rational64;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<S: Serializer>(rational: &Rational64, serializer: S) -> Result<S::Ok, S::Error> {
+    (rational.numer(), rational.denom()).serialize(serializer)
+}
+
+/// Reduces the fraction, so equal numbers always compare equal. Fractions that don't fit with a
+/// positive denominator, like `[i64::MIN, -1]`, are an error.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rational64, D::Error> {
+    let (numerator, denominator) = <(i64, i64)>::deserialize(deserializer)?;
+    if denominator == 0 {
+        return Err(D::Error::custom("the denominator of a rational is zero"));
+    }
+    let (numerator, denominator) = if denominator < 0 {
+        match (numerator.checked_neg(), denominator.checked_neg()) {
+            (Some(numerator), Some(denominator)) => (numerator, denominator),
+            _ => return Err(D::Error::custom("the rational overflows")),
+        }
+    } else {
+        (numerator, denominator)
+    };
+    Ok(Rational64::new(numerator, denominator))
+}
@@ -1,4 +1,4 @@
-use super::{Equation, Variable};
+use super::{Assumptions, Equation, Variable};
 use crate::math::steps::StepLogger;
 use num_rational::Rational64;
 use std::collections::BTreeMap;
@@ -11,7 +11,7 @@ fn flatten_addition(addition: Vec<Equation>) -> Vec<Equation> {
                 new_addition.append(&mut flatten_addition(a));
             }
             other => new_addition.push(other),
-        };
+        }
     }
     new_addition
 }
@@ -20,10 +20,13 @@ fn flatten_addition(addition: Vec<Equation>) -> Vec<Equation> {
 #[allow(clippy::no_effect)]
 pub(super) fn simplify_addition(
     mut addition: Vec<Equation>,
+    assumptions: &Assumptions,
     step_logger: &mut Option<StepLogger>,
 ) -> Equation {
     if addition.len() == 1 {
-        return addition.remove(0).simplify(step_logger);
+        return addition
+            .remove(0)
+            .simplify_with_assumptions(assumptions, step_logger);
     }
     let addition = flatten_addition(addition);
     let mut total_rational_term: Rational64 = 0.into();
@@ -32,7 +35,7 @@ pub(super) fn simplify_addition(
     let mut cos_squares: BTreeMap<Equation, Rational64> = BTreeMap::new();
 
     for equation in addition {
-        let (term, count) = match equation.simplify(step_logger) {
+        let (term, count) = match equation.simplify_with_assumptions(assumptions, step_logger) {
             Equation::Variable(Variable::Integer(0)) => continue,
             Equation::Variable(Variable::Integer(i)) => {
                 total_rational_term += i;
@@ -72,35 +75,31 @@ pub(super) fn simplify_addition(
                     .into_iter()
                     .filter(|x| x.get_number_or_none().is_none())
                     .collect();
-                (Equation::Multiplication(term).simplify(step_logger), count)
+                (
+                    Equation::Multiplication(term)
+                        .simplify_with_assumptions(assumptions, step_logger),
+                    count,
+                )
             }
             Equation::Negative(negative) => (*negative, Rational64::new(-1, 1)),
-            Equation::Power(ref power)
-                if power.1 == Equation::Variable(Variable::Integer(2))
-                    && matches!(&power.0, Equation::Sin(_)) =>
-            {
-                let Equation::Sin(ref sin) = power.0 else {
-                    unreachable!()
-                };
-                let previous_count = *sin_squares.get(sin).unwrap_or(&0.into());
-                sin_squares.insert(*sin.clone(), previous_count + 1);
-                continue;
-            }
-            Equation::Power(ref power)
-                if power.1 == Equation::Variable(Variable::Integer(2))
-                    && matches!(&power.0, Equation::Cos(_)) =>
-            {
-                let Equation::Cos(ref cos) = power.0 else {
-                    unreachable!()
-                };
-                let previous_count = *cos_squares.get(cos).unwrap_or(&0.into());
-                cos_squares.insert(*cos.clone(), previous_count + 1);
-                continue;
-            }
-
             other => (other, 1.into()),
         };
 
+        let squares = match &term {
+            Equation::Power(power) if power.1 == Equation::Variable(Variable::Integer(2)) => {
+                match &power.0 {
+                    Equation::Sin(sin) => Some((&mut sin_squares, (**sin).clone())),
+                    Equation::Cos(cos) => Some((&mut cos_squares, (**cos).clone())),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some((squares, argument)) = squares {
+            *squares.entry(argument).or_insert_with(|| 0.into()) += count;
+            continue;
+        }
+
         let previous_count = *terms.get(&term).unwrap_or(&0.into());
         terms.insert(term, previous_count + count);
     }
@@ -108,7 +107,8 @@ pub(super) fn simplify_addition(
     let mut simplified_addition: Vec<Equation> = Vec::new();
     if total_rational_term != 0.into() {
         simplified_addition.push(
-            Equation::Variable(Variable::Rational(total_rational_term)).simplify(step_logger),
+            Equation::Variable(Variable::Rational(total_rational_term))
+                .simplify_with_assumptions(assumptions, step_logger),
         );
     }
     for (equation, count) in terms {
@@ -117,33 +117,44 @@ pub(super) fn simplify_addition(
         } else {
             let next_term = Equation::Multiplication(vec![
                 equation,
-                Equation::Variable(Variable::Rational(count)).simplify(step_logger),
+                Equation::Variable(Variable::Rational(count))
+                    .simplify_with_assumptions(assumptions, step_logger),
             ])
-            .simplify(step_logger);
+            .simplify_with_assumptions(assumptions, step_logger);
             simplified_addition.push(next_term);
         }
     }
 
     for (sin, mut sin_count) in sin_squares {
         let mut cos_count = *cos_squares.get(&sin).unwrap_or(&0.into());
-        let number_of_ones = sin_count.min(cos_count);
+        // sin^2 + cos^2 = 1 only cancels terms with the same sign
+        let number_of_ones = if sin_count > 0.into() && cos_count > 0.into() {
+            sin_count.min(cos_count)
+        } else if sin_count < 0.into() && cos_count < 0.into() {
+            sin_count.max(cos_count)
+        } else {
+            0.into()
+        };
         cos_squares.remove(&sin);
         sin_count -= number_of_ones;
         cos_count -= number_of_ones;
         if number_of_ones != 0.into() {
-            simplified_addition
-                .push(Equation::Variable(Variable::Rational(number_of_ones)).simplify(step_logger));
+            simplified_addition.push(
+                Equation::Variable(Variable::Rational(number_of_ones))
+                    .simplify_with_assumptions(assumptions, step_logger),
+            );
         }
         if sin_count != 0.into() {
             simplified_addition.push(
                 Equation::Multiplication(vec![
-                    Equation::Variable(Variable::Rational(sin_count)).simplify(step_logger),
+                    Equation::Variable(Variable::Rational(sin_count))
+                        .simplify_with_assumptions(assumptions, step_logger),
                     Equation::Power(Box::new((
                         Equation::Sin(Box::new(sin.clone())),
                         Equation::Variable(Variable::Integer(2)),
                     ))),
                 ])
-                .simplify(step_logger),
+                .simplify_with_assumptions(assumptions, step_logger),
             );
         }
         if cos_count != 0.into() {
@@ -152,23 +163,25 @@ pub(super) fn simplify_addition(
                     Equation::Variable(Variable::Rational(cos_count)),
                     Equation::Power(Box::new((
                         Equation::Cos(Box::new(sin)),
-                        Equation::Variable(Variable::Integer(2)).simplify(step_logger),
+                        Equation::Variable(Variable::Integer(2))
+                            .simplify_with_assumptions(assumptions, step_logger),
                     ))),
                 ])
-                .simplify(step_logger),
+                .simplify_with_assumptions(assumptions, step_logger),
             );
         }
     }
     for (cos, cos_count) in cos_squares {
         simplified_addition.push(
             Equation::Multiplication(vec![
-                Equation::Variable(Variable::Rational(cos_count)).simplify(step_logger),
+                Equation::Variable(Variable::Rational(cos_count))
+                    .simplify_with_assumptions(assumptions, step_logger),
                 Equation::Power(Box::new((
                     Equation::Cos(Box::new(cos)),
                     Equation::Variable(Variable::Integer(2)),
                 ))),
             ])
-            .simplify(step_logger),
+            .simplify_with_assumptions(assumptions, step_logger),
         );
     }
 
@@ -176,5 +189,7 @@ pub(super) fn simplify_addition(
         return Equation::Variable(Variable::Integer(0));
     }
 
+    // Subtracted terms go last, so the sum reads as a-b instead of -b+a
+    simplified_addition.sort_by_key(|term| matches!(term, Equation::Negative(_)));
     Equation::Addition(simplified_addition)
 }
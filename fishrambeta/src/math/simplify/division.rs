@@ -1,13 +1,18 @@
-use super::{Equation, Variable};
+use super::{Assumptions, Equation, Variable};
 use crate::math::steps::StepLogger;
 use num_rational::Rational64;
 
 pub(super) fn simplify_division(
     division: (Equation, Equation),
+    assumptions: &Assumptions,
     step_logger: &mut Option<StepLogger>,
 ) -> Equation {
-    let mut numerator = division.0.simplify(step_logger);
-    let mut denominator = division.1.simplify(step_logger);
+    let mut numerator = division
+        .0
+        .simplify_with_assumptions(assumptions, step_logger);
+    let mut denominator = division
+        .1
+        .simplify_with_assumptions(assumptions, step_logger);
 
     match numerator {
         Equation::Division(division) => {
@@ -15,7 +20,7 @@ pub(super) fn simplify_division(
                 division.0,
                 Equation::Multiplication(vec![division.1, denominator]),
             )))
-            .simplify(step_logger);
+            .simplify_with_assumptions(assumptions, step_logger);
         }
         Equation::Variable(Variable::Rational(rational)) => {
             return Equation::Division(Box::new((
@@ -24,7 +29,7 @@ pub(super) fn simplify_division(
                     denominator,
                     Equation::Variable(Variable::Integer(*rational.denom())),
                 ])
-                .simplify(step_logger),
+                .simplify_with_assumptions(assumptions, step_logger),
             )));
         }
         Equation::Multiplication(ref mut multiplication) => {
@@ -43,7 +48,7 @@ pub(super) fn simplify_division(
                         denominator,
                         Equation::Variable(Variable::Integer(*rational.denom())),
                     ])
-                    .simplify(step_logger),
+                    .simplify_with_assumptions(assumptions, step_logger),
                 )));
             }
         }
@@ -55,7 +60,7 @@ pub(super) fn simplify_division(
                 Equation::Multiplication(vec![numerator, division.1]),
                 division.0,
             )))
-            .simplify(step_logger);
+            .simplify_with_assumptions(assumptions, step_logger);
         }
         Equation::Variable(Variable::Rational(rational)) => {
             return Equation::Division(Box::new((
@@ -87,7 +92,7 @@ pub(super) fn simplify_division(
                         numerator,
                         Equation::Variable(Variable::Integer(*rational.denom())),
                     ])
-                    .simplify(step_logger),
+                    .simplify_with_assumptions(assumptions, step_logger),
                     Equation::Multiplication(multiplication.clone()),
                 )));
             }
@@ -96,15 +101,28 @@ pub(super) fn simplify_division(
         _ => {}
     }
 
+    if let (Equation::Power(numerator), Equation::Power(denominator)) = (&numerator, &denominator) {
+        if numerator.0 == denominator.0 && assumptions.is_nonzero(&numerator.0) {
+            return Equation::Power(Box::new((
+                numerator.0.clone(),
+                numerator.1.clone() - denominator.1.clone(),
+            )))
+            .simplify_with_assumptions(assumptions, step_logger);
+        }
+    }
+
     for factor in denominator.shared_factors(&numerator) {
-        if numerator.has_factor(&factor) && denominator.has_factor(&factor) {
+        if numerator.has_factor(&factor)
+            && denominator.has_factor(&factor)
+            && assumptions.is_nonzero(&factor)
+        {
             numerator = numerator.remove_factor(&factor);
             denominator = denominator.remove_factor(&factor);
         }
     }
 
-    numerator = numerator.simplify(step_logger);
-    denominator = denominator.simplify(step_logger);
+    numerator = numerator.simplify_with_assumptions(assumptions, step_logger);
+    denominator = denominator.simplify_with_assumptions(assumptions, step_logger);
 
     if numerator == Equation::Variable(Variable::Integer(0)) {
         Equation::Variable(Variable::Integer(0))
@@ -1,46 +1,144 @@
 use crate::math::{
-    steps::{helpers::{close_step, open_step}, StepLogger},
-    Equation, Variable,
+    steps::{
+        helpers::{close_step, open_step},
+        StepLogger,
+    },
+    Assumptions, Constant, Equation, Variable,
 };
-use std::collections::BTreeMap;
+use num_rational::Rational64;
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
 
 mod addition;
 mod division;
 mod multiplication;
 mod power;
 
+/// How long [`Equation::simplify_with_options`] keeps simplifying
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimplifyOptions {
+    /// The most passes over the equation
+    pub max_iterations: usize,
+    /// Stops after the first pass that ends later than this after starting. Time can't be
+    /// measured on `wasm32-unknown-unknown`, so it has to be `None` there.
+    pub time_budget: Option<Duration>,
+    /// Stops when a pass returns an equation an earlier pass already returned
+    pub detect_cycles: bool,
+    pub assumptions: Assumptions,
+}
+
+/// Why [`Equation::simplify_with_options`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Convergence {
+    /// A pass didn't change the equation anymore
+    FixedPoint,
+    /// Passes went back to an equation seen before
+    Cycle,
+    MaxIterations,
+    TimeBudget,
+}
+
+/// A simplified equation together with why simplifying stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simplified {
+    pub equation: Equation,
+    pub convergence: Convergence,
+}
+
+impl Default for SimplifyOptions {
+    fn default() -> Self {
+        SimplifyOptions {
+            max_iterations: 64,
+            time_budget: None,
+            detect_cycles: true,
+            assumptions: Assumptions::new(),
+        }
+    }
+}
+
+impl Simplified {
+    /// Whether simplifying more wouldn't change the equation
+    pub fn reached_fixed_point(&self) -> bool {
+        self.convergence == Convergence::FixedPoint
+    }
+}
+
 impl Equation {
+    /// Simplifies with the default [`Assumptions`], so `\frac{x}{x}` is kept as it is undefined
+    /// at `x = 0`. Use [`Equation::simplify_until_complete_with_assumptions`] with
+    /// [`Assumptions::lenient`] to cancel it like ordinary algebra does.
     pub fn simplify_until_complete(self, step_logger: &mut Option<StepLogger>) -> Self {
-        let mut equation = self.clone();
-        let mut previous = self;
-        for _ in 1..5 {
-            equation = equation.simplify(step_logger);
-            if equation == previous {
-                break;
-            }
-            previous = equation.clone();
-        }
-        equation
+        self.simplify_with_options(&SimplifyOptions::default(), step_logger)
+            .equation
+    }
+
+    /// Simplifies using only the simplifications that are valid under the assumptions, so
+    /// `\sqrt{x^{2}}` becomes `x` when `x > 0` is assumed and `|x|` otherwise
+    pub fn simplify_until_complete_with_assumptions(
+        self,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Self {
+        let options = SimplifyOptions {
+            assumptions: assumptions.clone(),
+            ..SimplifyOptions::default()
+        };
+        self.simplify_with_options(&options, step_logger).equation
     }
 
-    pub fn simplify_until_complete_with_print(self, step_logger: &mut Option<StepLogger>) -> Self {
-        let mut equation = self.clone();
-        let mut previous = equation.to_latex();
-        for i in 1..5 {
-            equation = equation.simplify(step_logger);
-            println!("{i}: {equation}, {equation:?}");
-            if equation.to_latex() == previous {
-                break;
+    /// Simplifies pass after pass until a pass doesn't change the equation or one of the limits
+    /// of the options is hit
+    pub fn simplify_with_options(
+        self,
+        options: &SimplifyOptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Simplified {
+        let deadline = options.time_budget.map(|budget| Instant::now() + budget);
+        let mut seen = HashSet::new();
+        let mut equation = self;
+        for _ in 0..options.max_iterations {
+            let simplified = equation
+                .clone()
+                .simplify_with_assumptions(&options.assumptions, step_logger);
+            if simplified == equation {
+                return Simplified {
+                    equation,
+                    convergence: Convergence::FixedPoint,
+                };
+            }
+            if options.detect_cycles {
+                seen.insert(equation);
+                if seen.contains(&simplified) {
+                    return Simplified {
+                        equation: simplified,
+                        convergence: Convergence::Cycle,
+                    };
+                }
+            }
+            equation = simplified;
+            if deadline.is_some_and(|deadline| Instant::now() > deadline) {
+                return Simplified {
+                    equation,
+                    convergence: Convergence::TimeBudget,
+                };
             }
-            previous = equation.to_latex();
         }
-        equation
+        Simplified {
+            equation,
+            convergence: Convergence::MaxIterations,
+        }
     }
 
     pub(super) fn simplify(self, step_logger: &mut Option<StepLogger>) -> Self {
-        let calculated_wrapped = self.calculate_exact();
-        if calculated_wrapped.is_some() {
-            let calculated = calculated_wrapped.unwrap();
+        self.simplify_with_assumptions(&Assumptions::new(), step_logger)
+    }
+
+    pub(super) fn simplify_with_assumptions(
+        self,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Self {
+        if let Some(calculated) = self.calculate_exact() {
             let numerical_part = if calculated.is_integer() {
                 Equation::Variable(Variable::Integer(calculated.to_integer()))
             } else {
@@ -61,7 +159,9 @@ impl Equation {
                 variable => Equation::Variable(variable),
             },
             Equation::Negative(negative) => match *negative {
-                Equation::Negative(negative) => (*negative).simplify(&mut None),
+                Equation::Negative(negative) => {
+                    (*negative).simplify_with_assumptions(assumptions, &mut None)
+                }
                 Equation::Variable(Variable::Integer(0)) => {
                     Equation::Variable(Variable::Integer(0))
                 }
@@ -72,34 +172,82 @@ impl Equation {
                     Equation::Variable(Variable::Rational(-rational))
                 }
 
-                negative => Equation::Negative(Box::new(negative.simplify(&mut None))),
+                negative => Equation::Negative(Box::new(
+                    negative.simplify_with_assumptions(assumptions, &mut None),
+                )),
             },
-            Equation::Addition(addition) => addition::simplify_addition(addition, &mut None),
+            Equation::Addition(addition) => {
+                addition::simplify_addition(addition, assumptions, &mut None)
+            }
             Equation::Multiplication(multiplication) => {
-                multiplication::simplify_multiplication(multiplication, &mut None)
+                multiplication::simplify_multiplication(multiplication, assumptions, &mut None)
+            }
+            Equation::Division(division) => {
+                division::simplify_division(*division, assumptions, &mut None)
             }
-            Equation::Division(division) => division::simplify_division(*division, &mut None),
-            Equation::Power(power) => power::simplify_power(*power, &mut None),
-            Equation::Ln(ln) => Equation::Ln(Box::new(ln.simplify(&mut None))),
-            Equation::Sin(sin) => Equation::Sin(Box::new(sin.simplify(&mut None))),
-            Equation::Cos(cos) => Equation::Cos(Box::new(cos.simplify(&mut None))),
-            Equation::Arcsin(sin) => Equation::Arcsin(Box::new(sin.simplify(&mut None))),
-            Equation::Arccos(cos) => Equation::Arccos(Box::new(cos.simplify(&mut None))),
-            Equation::Arctan(tan) => Equation::Arctan(Box::new(tan.simplify(&mut None))),
-            Equation::Abs(abs) => Equation::Abs(Box::new(abs.simplify(&mut None))),
-            Equation::Equals(equation) => Equation::Equals(Box::new((
-                equation.0.simplify(&mut None),
-                equation.1.simplify(&mut None),
-            ))),
-            Equation::Derivative(_) => {
-                panic!("Derivative cannot be simplified")
+            Equation::Power(power) => power::simplify_power(*power, assumptions, &mut None),
+            Equation::Abs(abs) => simplify_abs(
+                abs.simplify_with_assumptions(assumptions, &mut None),
+                assumptions,
+            ),
+            Equation::Ln(ln) => match ln.simplify_with_assumptions(assumptions, &mut None) {
+                Equation::Power(power)
+                    if power.0 == Equation::Variable(Variable::Constant(Constant::E))
+                        && assumptions.is_real(&power.1) =>
+                {
+                    power.1
+                }
+                ln => Equation::Ln(Box::new(ln)),
+            },
+            Equation::Sin(_) | Equation::Cos(_) => {
+                let trig = self
+                    .map_children(|child| child.simplify_with_assumptions(assumptions, &mut None));
+                trig.exact_trig_value().unwrap_or(trig)
             }
+            equation => equation
+                .map_children(|child| child.simplify_with_assumptions(assumptions, &mut None)),
         };
         close_step(step_logger, &simplified);
         simplified
     }
 }
 
+/// Removes the absolute value of expressions that can't be negative, after taking out the sign
+/// of a negative numeric factor
+fn simplify_abs(abs: Equation, assumptions: &Assumptions) -> Equation {
+    match abs {
+        Equation::Negative(negative) => simplify_abs(*negative, assumptions),
+        Equation::Abs(abs) => Equation::Abs(abs),
+        Equation::Multiplication(factors)
+            if factors
+                .iter()
+                .any(|factor| negated_number(factor).is_some()) =>
+        {
+            let factors = factors
+                .into_iter()
+                .map(|factor| negated_number(&factor).unwrap_or(factor))
+                .collect();
+            simplify_abs(Equation::Multiplication(factors), assumptions)
+        }
+        abs if assumptions.is_nonnegative(&abs) => abs,
+        abs => Equation::Abs(Box::new(abs)),
+    }
+}
+
+/// The absolute value of a negative number
+fn negated_number(equation: &Equation) -> Option<Equation> {
+    let number = equation.calculate_exact()?;
+    if number >= 0.into() {
+        return None;
+    }
+    let negated = Rational64::new_raw(number.numer().checked_neg()?, *number.denom());
+    Some(if negated.is_integer() {
+        Equation::from(negated.to_integer())
+    } else {
+        Equation::from(negated)
+    })
+}
+
 pub struct EquationBTreeMap(BTreeMap<Equation, Vec<Equation>>);
 
 impl EquationBTreeMap {
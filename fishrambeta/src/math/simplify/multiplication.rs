@@ -1,4 +1,4 @@
-use super::{Equation, EquationBTreeMap, Variable};
+use super::{Assumptions, Equation, EquationBTreeMap, Variable};
 use crate::math::steps::StepLogger;
 use num_rational::Rational64;
 
@@ -10,23 +10,14 @@ fn flatten_multiplication(multiplication: Vec<Equation>) -> Vec<Equation> {
                 new_mult.append(&mut flatten_multiplication(m));
             }
             other => new_mult.push(other),
-        };
+        }
     }
     new_mult
 }
 
-fn distribute_terms(multiplication: &[Equation], addition: Vec<Equation>) -> Equation {
-    let mut new_addition = Vec::new();
-    for addition_term in addition {
-        let mut new_multiplication = multiplication.to_owned();
-        new_multiplication.push(addition_term);
-        new_addition.push(Equation::Multiplication(new_multiplication));
-    }
-    Equation::Addition(new_addition)
-}
-
 pub(super) fn simplify_multiplication(
     multiplication: Vec<Equation>,
+    assumptions: &Assumptions,
     step_logger: &mut Option<StepLogger>,
 ) -> Equation {
     let mut multiplication = flatten_multiplication(multiplication);
@@ -35,7 +26,10 @@ pub(super) fn simplify_multiplication(
 
     let mut total_is_negative = false;
     for (index, equation) in multiplication.iter().enumerate() {
-        let (term, count) = match equation.clone().simplify(step_logger) {
+        let (term, count) = match equation
+            .clone()
+            .simplify_with_assumptions(assumptions, step_logger)
+        {
             Equation::Variable(Variable::Integer(0)) => {
                 return Equation::Variable(Variable::Integer(0));
             }
@@ -70,10 +64,6 @@ pub(super) fn simplify_multiplication(
                     division.1,
                 )));
             }
-            Equation::Addition(addition) => {
-                multiplication.remove(index);
-                return distribute_terms(&multiplication, addition);
-            }
             term => (term, Equation::Variable(Variable::Integer(1))),
         };
         terms.insert_or_push(term, count);
@@ -84,25 +74,47 @@ pub(super) fn simplify_multiplication(
     if total_is_negative {
         total_rational_factor *= -1;
     }
+    // -x\cdot y instead of -1\cdot x\cdot y
+    let is_negated = total_rational_factor == (-1).into() && !terms.0.is_empty();
+    if is_negated {
+        total_rational_factor = 1.into();
+    }
     if total_rational_factor != 1.into() || terms.0.is_empty() {
         simplified_multiplication.push(
-            Equation::Variable(Variable::Rational(total_rational_factor)).simplify(step_logger),
+            Equation::Variable(Variable::Rational(total_rational_factor))
+                .simplify_with_assumptions(assumptions, step_logger),
         );
     }
 
     for (term, count) in terms.0 {
+        let exponent =
+            Equation::Addition(count.clone()).simplify_with_assumptions(assumptions, step_logger);
+        // x\cdot x^{-1} is only 1 when x isn't zero
+        if exponent == Equation::Variable(Variable::Integer(0))
+            && count.len() > 1
+            && !assumptions.is_nonzero(&term)
+        {
+            simplified_multiplication.extend(
+                count
+                    .into_iter()
+                    .map(|count| Equation::Power(Box::new((term.clone(), count)))),
+            );
+            continue;
+        }
         simplified_multiplication.push(
-            Equation::Power(Box::new((
-                term,
-                Equation::Addition(count).simplify(step_logger),
-            )))
-            .simplify(step_logger),
+            Equation::Power(Box::new((term, exponent)))
+                .simplify_with_assumptions(assumptions, step_logger),
         );
     }
 
-    if simplified_multiplication.len() == 1 {
-        return simplified_multiplication.remove(0);
+    let simplified = if simplified_multiplication.len() == 1 {
+        simplified_multiplication.remove(0)
+    } else {
+        Equation::Multiplication(simplified_multiplication)
+    };
+    if is_negated {
+        Equation::Negative(Box::new(simplified))
+    } else {
+        simplified
     }
-
-    Equation::Multiplication(simplified_multiplication)
 }
@@ -1,14 +1,16 @@
 use crate::math::steps::StepLogger;
 
-use super::{Equation, Variable};
+use super::{Assumptions, Equation, Variable};
+use crate::math::Constant;
 use num::Signed;
 
 pub(super) fn simplify_power(
     power: (Equation, Equation),
+    assumptions: &Assumptions,
     step_logger: &mut Option<StepLogger>,
 ) -> Equation {
-    let base = power.0.simplify(step_logger);
-    let exponent = power.1.simplify(step_logger);
+    let base = power.0.simplify_with_assumptions(assumptions, step_logger);
+    let exponent = power.1.simplify_with_assumptions(assumptions, step_logger);
 
     if let Some(n) = exponent.get_number_or_none() {
         if n == 1.into() {
@@ -18,8 +20,18 @@ pub(super) fn simplify_power(
         }
     }
 
+    if base == Equation::Variable(Variable::Constant(Constant::E)) {
+        if let Some(exponential) = simplify_exponential(&exponent, assumptions) {
+            return exponential.simplify_with_assumptions(assumptions, step_logger);
+        }
+    }
+
     match base {
-        Equation::Multiplication(terms) => {
+        // (ab)^c = a^c b^c only holds for integer c or when the factors can't be negative
+        Equation::Multiplication(terms)
+            if assumptions.is_integer(&exponent)
+                || terms.iter().all(|term| assumptions.is_nonnegative(term)) =>
+        {
             let mut simplified_power: Vec<Equation> = vec![];
             for term in terms {
                 simplified_power.push(Equation::Power(Box::new((term, exponent.clone()))));
@@ -29,10 +41,20 @@ pub(super) fn simplify_power(
         Equation::Power(ref power) => {
             if let Some(e1) = exponent.get_number_or_none() {
                 if let Some(e2) = power.1.get_number_or_none() {
-                    return Equation::Power(Box::new((
-                        power.0.clone(),
-                        Equation::Variable(Variable::Rational(e1 * e2)),
-                    )));
+                    let exponent = Equation::Variable(Variable::Rational(e1 * e2));
+                    // (x^a)^b = x^{ab} only holds for integer b or nonnegative x, an even a
+                    // makes x^a nonnegative so (x^a)^b = |x|^{ab}
+                    if e1.is_integer() || assumptions.is_nonnegative(&power.0) {
+                        return Equation::Power(Box::new((power.0.clone(), exponent)));
+                    }
+                    if e2.is_integer() && e2.to_integer() % 2 == 0 && assumptions.is_real(&power.0)
+                    {
+                        return Equation::Power(Box::new((
+                            Equation::Abs(Box::new(power.0.clone())),
+                            exponent,
+                        )))
+                        .simplify_with_assumptions(assumptions, step_logger);
+                    }
                 }
             }
         }
@@ -77,7 +99,8 @@ pub(super) fn simplify_power(
                 Equation::Variable(Variable::Integer(1)),
                 Equation::Power(Box::new((
                     base,
-                    Equation::Variable(Variable::Rational(n.abs())).simplify(step_logger),
+                    Equation::Variable(Variable::Rational(n.abs()))
+                        .simplify_with_assumptions(assumptions, step_logger),
                 ))),
             )));
         }
@@ -85,3 +108,45 @@ pub(super) fn simplify_power(
 
     Equation::Power(Box::new((base, exponent)))
 }
+
+/// Writes the logarithms in the exponent of `e` as powers, `e^{x+c\cdot \ln(a)} = a^{c}\cdot e^{x}`
+/// for positive `a` and real `c`, or `None` if there are none
+fn simplify_exponential(exponent: &Equation, assumptions: &Assumptions) -> Option<Equation> {
+    let terms = match exponent {
+        Equation::Addition(terms) => terms.clone(),
+        exponent => vec![exponent.clone()],
+    };
+    let (powers, remaining): (Vec<_>, Vec<_>) = terms
+        .into_iter()
+        .map(|term| power_of_ln(&term, assumptions).ok_or(term))
+        .partition(Result::is_ok);
+    if powers.is_empty() {
+        return None;
+    }
+    let mut factors: Vec<Equation> = powers.into_iter().filter_map(Result::ok).collect();
+    factors.push(Equation::Power(Box::new((
+        Equation::Variable(Variable::Constant(Constant::E)),
+        Equation::Addition(remaining.into_iter().filter_map(Result::err).collect()),
+    ))));
+    Some(Equation::Multiplication(factors))
+}
+
+/// `a^{c}` for a term `c\cdot \ln(a)` of an exponent of `e`
+fn power_of_ln(term: &Equation, assumptions: &Assumptions) -> Option<Equation> {
+    let (ln, coefficient) = match term {
+        Equation::Ln(ln) => ((**ln).clone(), Equation::Variable(Variable::Integer(1))),
+        Equation::Multiplication(factors) => {
+            let index = factors
+                .iter()
+                .position(|factor| matches!(factor, Equation::Ln(_)))?;
+            let mut coefficient = factors.clone();
+            let Equation::Ln(ln) = coefficient.remove(index) else {
+                unreachable!()
+            };
+            (*ln, Equation::Multiplication(coefficient))
+        }
+        _ => return None,
+    };
+    (assumptions.is_positive(&ln) && assumptions.is_real(&coefficient))
+        .then(|| Equation::Power(Box::new((ln, coefficient))))
+}
@@ -106,6 +106,31 @@ impl StepLogger {
     pub fn set_message(&mut self, message: &str) {
         self.current_step_stack.last_mut().unwrap().message = Some(message.to_string());
     }
+
+    /// The steps that have been closed, in the order they were taken
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+impl Step {
+    pub fn equation_before(&self) -> &Equation {
+        &self.equation_before
+    }
+
+    pub fn equation_after(&self) -> &Equation {
+        self.equation_after
+            .as_ref()
+            .expect("Steps are only stored once they are closed")
+    }
+
+    pub fn sub_steps(&self) -> &[Step] {
+        &self.sub_steps
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
 }
 impl fmt::Display for StepLogger {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -121,33 +146,26 @@ impl fmt::Display for StepLogger {
 
 impl fmt::Display for Step {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self._to_string(0))
+        self.write_with_depth(f, 0)
     }
 }
 
 impl Step {
-    fn _to_string(&self, depth: usize) -> String {
-        let mut stringified = "\\textbf{ ".to_string();
-        for _ in 0..depth {
-            stringified.push('-');
+    fn write_with_depth(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        write!(f, "\\textbf{{ {}}}", "-".repeat(depth))?;
+        if let Some(message) = &self.message {
+            write!(f, "\\textbf{{{message}: }}")?;
         }
-        stringified += "}";
-        stringified += &match &self.message {
-            Some(message) => format!(
-                "\\textbf{{{}: }}{} \\textbf{{ => }} {}",
-                message,
-                self.equation_before,
-                self.equation_after.as_ref().unwrap()
-            ),
-            None => format!(
-                "{} \\textbf{{ => }} {}",
-                self.equation_before,
-                self.equation_after.as_ref().unwrap()
-            ),
-        };
-        self.sub_steps
-            .iter()
-            .for_each(|step| stringified.push_str(&format!("\n{}", step._to_string(depth + 1))));
-        stringified
+        write!(
+            f,
+            "{} \\textbf{{ => }} {}",
+            self.equation_before,
+            self.equation_after.as_ref().unwrap()
+        )?;
+        for step in &self.sub_steps {
+            writeln!(f)?;
+            step.write_with_depth(f, depth + 1)?;
+        }
+        Ok(())
     }
 }
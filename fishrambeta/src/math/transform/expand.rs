@@ -0,0 +1,115 @@
+use super::{sum, terms, Monomial};
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::Equation;
+use num_rational::Rational64;
+use std::collections::BTreeMap;
+
+impl Equation {
+    /// Multiplies out products and positive integer powers of sums, so `(x+1)^{2}` becomes
+    /// `x^{2}+2\cdot x+1`. Products whose coefficients don't fit an `i64`, like `(x+1)^{70}`,
+    /// are left as they are.
+    pub fn expand(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Expand"));
+        let expanded = self.expanded().simplify_until_complete(&mut None);
+        close_step(step_logger, &expanded);
+        expanded
+    }
+
+    fn expanded(self) -> Equation {
+        match self.map_children(Equation::expanded) {
+            Equation::Multiplication(factors) => {
+                distribute(factors.clone()).unwrap_or(Equation::Multiplication(factors))
+            }
+            Equation::Power(power) => match power.1.get_integer_or_none() {
+                Some(exponent @ 2..) if matches!(power.0, Equation::Addition(_)) => {
+                    let factors = (0..exponent).map(|_| power.0.clone()).collect();
+                    distribute(factors).unwrap_or(Equation::Power(power))
+                }
+                _ => Equation::Power(power),
+            },
+            Equation::Negative(negative) => match *negative {
+                Equation::Addition(terms) => {
+                    Equation::Addition(terms.into_iter().map(|term| -term).collect())
+                }
+                negative => -negative,
+            },
+            Equation::Division(division) => match division.0 {
+                Equation::Addition(terms) => Equation::Addition(
+                    terms
+                        .into_iter()
+                        .map(|term| term / division.1.clone())
+                        .collect(),
+                ),
+                numerator => numerator / division.1,
+            },
+            equation => equation,
+        }
+    }
+}
+
+/// The sum of the products of one term of every factor, multiplying in one factor at a time and
+/// adding up like terms after every factor, so `(x+1)^{n}` has `n+1` terms instead of `2^{n}`.
+/// `None` when a coefficient overflows.
+fn distribute(factors: Vec<Equation>) -> Option<Equation> {
+    let mut products = vec![Monomial::new(1.into())];
+    for factor in factors {
+        let factor_terms = terms(factor)
+            .iter()
+            .map(Monomial::from_equation)
+            .collect::<Vec<_>>();
+        let multiplied = products
+            .iter()
+            .flat_map(|product| factor_terms.iter().map(|term| checked_mul(product, term)))
+            .collect::<Option<Vec<_>>>()?;
+        products = combine_like_terms(multiplied)?;
+    }
+    Some(sum(products
+        .into_iter()
+        .map(Monomial::into_equation)
+        .collect()))
+}
+
+/// Adds up the coefficients of terms with the same powers, or `None` when one overflows
+fn combine_like_terms(terms: Vec<Monomial>) -> Option<Vec<Monomial>> {
+    let mut coefficients: BTreeMap<BTreeMap<Equation, i64>, Rational64> = BTreeMap::new();
+    for term in terms {
+        let coefficient = coefficients.entry(term.powers).or_default();
+        *coefficient = checked_add(*coefficient, term.coefficient)?;
+    }
+    Some(
+        coefficients
+            .into_iter()
+            .filter(|(_, coefficient)| *coefficient != 0.into())
+            .map(|(powers, coefficient)| Monomial {
+                coefficient,
+                powers,
+            })
+            .collect(),
+    )
+}
+
+fn checked_mul(a: &Monomial, b: &Monomial) -> Option<Monomial> {
+    let coefficient = Rational64::new(
+        a.coefficient.numer().checked_mul(*b.coefficient.numer())?,
+        a.coefficient.denom().checked_mul(*b.coefficient.denom())?,
+    );
+    Some(b.powers.iter().fold(
+        Monomial {
+            coefficient,
+            powers: a.powers.clone(),
+        },
+        |product, (base, &exponent)| product.with_power(base.clone(), exponent),
+    ))
+}
+
+fn checked_add(a: Rational64, b: Rational64) -> Option<Rational64> {
+    let numerator = a
+        .numer()
+        .checked_mul(*b.denom())?
+        .checked_add(b.numer().checked_mul(*a.denom())?)?;
+    Some(Rational64::new(
+        numerator,
+        a.denom().checked_mul(*b.denom())?,
+    ))
+}
@@ -0,0 +1,114 @@
+use super::univariate::Univariate;
+use super::{product, sum, Monomial};
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::Equation;
+use num_integer::Integer;
+use num_rational::Rational64;
+
+impl Equation {
+    /// Pulls the common factors out of sums and splits polynomials in one variable at their
+    /// rational roots, so `x^{3}-x` becomes `x\cdot (x-1)\cdot (x+1)`
+    pub fn factor(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Factor"));
+        let factored = self.simplify_until_complete(&mut None).factored();
+        close_step(step_logger, &factored);
+        factored
+    }
+
+    fn factored(self) -> Equation {
+        match self.map_children(Equation::factored) {
+            Equation::Addition(terms) => factor_sum(terms),
+            equation => equation,
+        }
+    }
+}
+
+fn factor_sum(terms: Vec<Equation>) -> Equation {
+    let monomials: Vec<Monomial> = terms.iter().map(Monomial::from_equation).collect();
+    if monomials.iter().any(|m| m.coefficient == 0.into()) {
+        return Equation::Addition(terms);
+    }
+    let numerators = monomials
+        .iter()
+        .fold(0, |gcd, m| m.coefficient.numer().gcd(&gcd));
+    let denominators = monomials
+        .iter()
+        .fold(1, |lcm, m| m.coefficient.denom().lcm(&lcm));
+    let sign = if monomials.iter().all(|m| m.coefficient < 0.into()) {
+        -1
+    } else {
+        1
+    };
+    let mut common = Monomial::new(Rational64::new(sign * numerators, denominators));
+    for (base, &exponent) in &monomials[0].powers {
+        let shared = monomials
+            .iter()
+            .map(|m| m.powers.get(base).copied().unwrap_or(0))
+            .fold(exponent, i64::min);
+        if shared > 0 {
+            common = common.with_power(base.clone(), shared);
+        }
+    }
+    let remaining = sum(monomials
+        .into_iter()
+        .map(|m| m.mul(&common.clone().reciprocal()).into_equation())
+        .collect())
+    .simplify_until_complete(&mut None);
+    let remaining = factor_polynomial(remaining);
+    if common.is_one() {
+        return remaining;
+    }
+    let mut factors = match common.into_equation() {
+        Equation::Multiplication(factors) => factors,
+        factor => vec![factor],
+    };
+    match remaining {
+        Equation::Multiplication(remaining) => factors.extend(remaining),
+        remaining => factors.push(remaining),
+    }
+    product(factors)
+}
+
+/// Splits a polynomial in its only variable into `(q\cdot x-p)^{m}` factors for its rational
+/// roots `\frac{p}{q}`, and the factor without rational roots
+fn factor_polynomial(equation: Equation) -> Equation {
+    let variables = equation.free_variables();
+    let (Some(variable), 1) = (variables.first(), variables.len()) else {
+        return equation;
+    };
+    let Some(polynomial) = Univariate::from_equation(&equation, variable) else {
+        return equation;
+    };
+    if polynomial.degree() < 2 {
+        return equation;
+    }
+    let mut rest = polynomial.clone();
+    let mut factors = Vec::new();
+    for root in polynomial.rational_roots() {
+        let linear = Univariate::linear(root);
+        let mut multiplicity = 0;
+        while rest.degree() > 0 && rest.evaluate(root) == 0.into() {
+            rest = rest.div_rem(&linear).0;
+            multiplicity += 1;
+        }
+        // x - p/q = (q x - p)/q
+        let denominator = Rational64::from(*root.denom());
+        rest = rest.scale(denominator.recip().pow(multiplicity));
+        let factor = linear.scale(denominator).to_equation(variable);
+        factors.push(if multiplicity == 1 {
+            factor
+        } else {
+            factor.pow(i64::from(multiplicity))
+        });
+    }
+    if factors.is_empty() {
+        return equation;
+    }
+    match rest.degree() {
+        0 if rest.leading() == 1.into() => {}
+        0 => factors.insert(0, Equation::from(rest.leading())),
+        _ => factors.push(rest.to_equation(variable)),
+    }
+    product(factors)
+}
@@ -0,0 +1,114 @@
+use super::univariate::Univariate;
+use super::{sum, Monomial};
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::{Equation, Variable};
+use num_integer::Integer;
+
+impl Equation {
+    /// Writes sums of fractions as a single fraction over their least common denominator, so
+    /// `\frac{1}{x}+\frac{1}{y}` becomes `\frac{x+y}{x\cdot y}`
+    pub fn together(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Combine fractions"));
+        let combined = self.simplify_until_complete(&mut None).combined();
+        close_step(step_logger, &combined);
+        combined
+    }
+
+    fn combined(self) -> Equation {
+        match self.map_children(Equation::combined) {
+            Equation::Addition(terms) => combine_fractions(terms),
+            equation => equation,
+        }
+    }
+
+    /// Splits a fraction of polynomials in `variable` into a polynomial and fractions with
+    /// powers of the factors of the denominator below them, so `\frac{1}{x^{2}-1}` becomes
+    /// `\frac{1}{2\cdot (x-1)}-\frac{1}{2\cdot (x+1)}`. Only numbers may be coefficients, and
+    /// factors of the denominator without rational roots are kept together.
+    pub fn apart(self, variable: &Variable, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Split into partial fractions"));
+        let apart = self.partial_fractions(variable).unwrap_or(self);
+        close_step(step_logger, &apart);
+        apart
+    }
+
+    fn partial_fractions(&self, variable: &Variable) -> Option<Equation> {
+        let Equation::Division(division) = self.clone().together(&mut None) else {
+            return None;
+        };
+        let numerator = Univariate::from_equation(&division.0, variable)?;
+        let denominator = Univariate::from_equation(&division.1, variable)?;
+        if denominator.degree() == 0 {
+            return None;
+        }
+        let (quotient, remainder) = numerator.div_rem(&denominator);
+        let leading = denominator.leading().recip();
+        let (denominator, remainder) = (denominator.scale(leading), remainder.scale(leading));
+
+        let mut terms = vec![quotient.to_equation(variable)];
+        let mut rest = denominator.clone();
+        let mut left = remainder.clone();
+        for root in denominator.rational_roots() {
+            let linear = Univariate::linear(root);
+            let mut multiplicity = 0;
+            while rest.degree() > 0 && rest.evaluate(root) == 0.into() {
+                rest = rest.div_rem(&linear).0;
+                multiplicity += 1;
+            }
+            // The coefficients of (x - root)^{-k} are the Taylor coefficients of the remainder
+            // over the other factors around the root
+            let cofactor = denominator.div_rem(&linear.pow(multiplicity)).0;
+            let series = remainder
+                .shifted(root)
+                .series_divide(&cofactor.shifted(root), multiplicity);
+            for (j, coefficient) in series.into_iter().enumerate() {
+                let power = multiplicity - j;
+                let below = denominator.div_rem(&linear.pow(power)).0;
+                left = &left - &below.scale(coefficient);
+                let power = i64::try_from(power).expect("The degree fits an i64");
+                terms.push(
+                    Monomial::new(coefficient)
+                        .with_power(linear.to_equation(variable), -power)
+                        .into_equation(),
+                );
+            }
+        }
+        let linear_part = denominator.div_rem(&rest).0;
+        let irreducible = left.div_rem(&linear_part).0;
+        if !irreducible.is_zero() {
+            terms.push(irreducible.to_equation(variable) / rest.to_equation(variable));
+        }
+        let terms = terms
+            .into_iter()
+            .filter(|term| *term != Equation::from(0))
+            .collect();
+        Some(sum(terms))
+    }
+}
+
+/// Writes every term over the least common denominator and adds the numerators
+fn combine_fractions(terms: Vec<Equation>) -> Equation {
+    let monomials: Vec<Monomial> = terms.iter().map(Monomial::from_equation).collect();
+    let lcm = monomials
+        .iter()
+        .fold(1, |lcm, m| m.coefficient.denom().lcm(&lcm));
+    let mut denominator = Monomial::new(lcm.into());
+    for monomial in &monomials {
+        for (base, &exponent) in &monomial.powers {
+            let power = denominator.powers.get(base).copied().unwrap_or(0);
+            if -exponent > power {
+                denominator = denominator.with_power(base.clone(), -exponent - power);
+            }
+        }
+    }
+    if denominator.is_one() {
+        return Equation::Addition(terms);
+    }
+    let numerator = sum(monomials
+        .into_iter()
+        .map(|m| m.mul(&denominator).into_equation())
+        .collect())
+    .simplify_until_complete(&mut None);
+    Equation::Division(Box::new((numerator, denominator.into_equation())))
+}
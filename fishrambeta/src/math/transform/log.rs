@@ -0,0 +1,168 @@
+use super::{product, sum, Monomial};
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::{Assumptions, Equation};
+use num_rational::Rational64;
+
+impl Equation {
+    /// Splits logarithms of products, quotients and powers into sums of logarithms, so
+    /// `\ln(\frac{a\cdot b^{2}}{c})` becomes `\ln(a)+2\cdot \ln(b)-\ln(c)`. This only happens for
+    /// factors that are positive under the assumptions, as `\ln(a\cdot b) = \ln(a)+\ln(b)` doesn't
+    /// hold for negative `a` and `b`.
+    pub fn expand_log(
+        self,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Equation {
+        open_step(step_logger, &self, Some("Expand logarithms"));
+        let expanded = self
+            .simplify_until_complete_with_assumptions(assumptions, &mut None)
+            .log_expanded(assumptions, step_logger)
+            .simplify_until_complete_with_assumptions(assumptions, &mut None);
+        close_step(step_logger, &expanded);
+        expanded
+    }
+
+    fn log_expanded(
+        self,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Equation {
+        match self.map_children(|child| child.log_expanded(assumptions, step_logger)) {
+            Equation::Ln(argument) => expand_ln(*argument, assumptions, step_logger),
+            equation => equation,
+        }
+    }
+
+    /// Merges sums of multiples of logarithms into a single logarithm, so
+    /// `\ln(a)+2\cdot \ln(b)-\ln(c)` becomes `\ln(\frac{a\cdot b^{2}}{c})`. Logarithms of
+    /// expressions that aren't positive under the assumptions are left alone.
+    pub fn combine_log(
+        self,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Equation {
+        open_step(step_logger, &self, Some("Combine logarithms"));
+        let combined = self
+            .simplify_until_complete_with_assumptions(assumptions, &mut None)
+            .log_combined(assumptions, step_logger);
+        close_step(step_logger, &combined);
+        combined
+    }
+
+    fn log_combined(
+        self,
+        assumptions: &Assumptions,
+        step_logger: &mut Option<StepLogger>,
+    ) -> Equation {
+        let mut combine_children = |term: Equation| {
+            term.map_children(|child| child.log_combined(assumptions, step_logger))
+        };
+        match self {
+            Equation::Addition(terms) => {
+                let terms = terms.into_iter().map(&mut combine_children).collect();
+                combine_ln(terms, assumptions, step_logger)
+            }
+            term @ (Equation::Multiplication(_) | Equation::Negative(_)) => {
+                let term = combine_children(term);
+                combine_ln(vec![term], assumptions, step_logger)
+            }
+            equation => combine_children(equation),
+        }
+    }
+}
+
+/// `\ln` of `argument` as a sum of the logarithms of its positive factors
+fn expand_ln(
+    argument: Equation,
+    assumptions: &Assumptions,
+    step_logger: &mut Option<StepLogger>,
+) -> Equation {
+    let before = argument.clone().ln();
+    let (expanded, message) = match argument {
+        Equation::Multiplication(factors) => {
+            let (positive, rest): (Vec<_>, Vec<_>) = factors
+                .into_iter()
+                .partition(|factor| assumptions.is_positive(factor));
+            if positive.is_empty() {
+                return before;
+            }
+            let mut logarithms: Vec<Equation> = positive
+                .into_iter()
+                .map(|factor| expand_ln(factor, assumptions, step_logger))
+                .collect();
+            if !rest.is_empty() {
+                logarithms.push(product(rest).ln());
+            }
+            (sum(logarithms), "Logarithm of a product")
+        }
+        Equation::Division(division) if assumptions.is_positive(&division.1) => {
+            let (numerator, denominator) = *division;
+            let expanded = expand_ln(numerator, assumptions, step_logger)
+                - expand_ln(denominator, assumptions, step_logger);
+            (expanded, "Logarithm of a quotient")
+        }
+        Equation::Power(power)
+            if assumptions.is_positive(&power.0) && assumptions.is_real(&power.1) =>
+        {
+            let (base, exponent) = *power;
+            let expanded = exponent * expand_ln(base, assumptions, step_logger);
+            (expanded, "Logarithm of a power")
+        }
+        argument => return argument.ln(),
+    };
+    open_step(step_logger, &before, Some(message));
+    close_step(step_logger, &expanded);
+    expanded
+}
+
+/// Merges the terms `c\cdot \ln(a)` with a number `c` and positive `a` into one logarithm
+fn combine_ln(
+    terms: Vec<Equation>,
+    assumptions: &Assumptions,
+    step_logger: &mut Option<StepLogger>,
+) -> Equation {
+    let mut logarithms = Vec::new();
+    let mut rest = Vec::new();
+    for term in terms {
+        match ln_multiple(&term) {
+            Some((argument, coefficient)) if assumptions.is_positive(&argument) => {
+                logarithms.push((term, argument, coefficient));
+            }
+            _ => rest.push(term),
+        }
+    }
+    let single = matches!(logarithms.as_slice(), [(_, _, coefficient)] if *coefficient == 1.into());
+    if logarithms.is_empty() || single {
+        rest.extend(logarithms.into_iter().map(|(term, _, _)| term));
+        return sum(rest);
+    }
+    let before = sum(logarithms.iter().map(|(term, _, _)| term.clone()).collect());
+    let mut powers = Monomial::new(1.into());
+    let mut roots = Vec::new();
+    for (_, argument, coefficient) in logarithms {
+        if coefficient.is_integer() {
+            powers = powers.with_power(argument, coefficient.to_integer());
+        } else {
+            roots.push(argument.pow(coefficient));
+        }
+    }
+    let mut factors = vec![powers.into_equation()];
+    factors.extend(roots);
+    let combined = product(factors)
+        .simplify_until_complete_with_assumptions(assumptions, &mut None)
+        .ln();
+    open_step(step_logger, &before, Some("Sum of logarithms"));
+    close_step(step_logger, &combined);
+    rest.push(combined);
+    sum(rest)
+}
+
+/// The argument and number `c` of a term `c\cdot \ln(a)`
+fn ln_multiple(term: &Equation) -> Option<(Equation, Rational64)> {
+    let monomial = Monomial::from_equation(term);
+    match monomial.powers.into_iter().collect::<Vec<_>>().as_slice() {
+        [(Equation::Ln(argument), 1)] => Some(((**argument).clone(), monomial.coefficient)),
+        _ => None,
+    }
+}
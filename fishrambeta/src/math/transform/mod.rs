@@ -0,0 +1,202 @@
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::{Equation, Variable};
+use num_rational::Rational64;
+use std::collections::BTreeMap;
+
+mod expand;
+mod factor;
+mod fractions;
+mod log;
+mod trig;
+mod univariate;
+
+/// A rational coefficient times integer powers of other factors, like `\frac{3x^{2}}{y+1}`,
+/// with the denominator as negative powers
+#[derive(Debug, Clone)]
+struct Monomial {
+    coefficient: Rational64,
+    powers: BTreeMap<Equation, i64>,
+}
+
+impl Monomial {
+    fn new(coefficient: Rational64) -> Self {
+        Monomial {
+            coefficient,
+            powers: BTreeMap::new(),
+        }
+    }
+
+    fn from_equation(equation: &Equation) -> Self {
+        if let Some(number) = equation.get_number_or_none() {
+            return Monomial::new(number);
+        }
+        match equation {
+            Equation::Negative(negative) => {
+                Monomial::from_equation(negative).mul(&Monomial::new(Rational64::from(-1)))
+            }
+            Equation::Multiplication(factors) => factors
+                .iter()
+                .fold(Monomial::new(1.into()), |product, factor| {
+                    product.mul(&Monomial::from_equation(factor))
+                }),
+            Equation::Division(division) => Monomial::from_equation(&division.0)
+                .mul(&Monomial::from_equation(&division.1).reciprocal()),
+            Equation::Power(power) => match power.1.get_integer_or_none() {
+                Some(exponent) => Monomial::from_equation(&power.0).pow(exponent),
+                None => Monomial::new(1.into()).with_power(equation.clone(), 1),
+            },
+            equation => Monomial::new(1.into()).with_power(equation.clone(), 1),
+        }
+    }
+
+    fn with_power(mut self, base: Equation, exponent: i64) -> Self {
+        let total = self.powers.get(&base).copied().unwrap_or(0) + exponent;
+        if total == 0 {
+            self.powers.remove(&base);
+        } else {
+            self.powers.insert(base, total);
+        }
+        self
+    }
+
+    fn mul(self, other: &Monomial) -> Self {
+        let coefficient = self.coefficient * other.coefficient;
+        other.powers.iter().fold(
+            Monomial {
+                coefficient,
+                ..self
+            },
+            |product, (base, &exponent)| product.with_power(base.clone(), exponent),
+        )
+    }
+
+    fn reciprocal(self) -> Self {
+        Monomial {
+            coefficient: if self.coefficient == 0.into() {
+                self.coefficient
+            } else {
+                self.coefficient.recip()
+            },
+            powers: self
+                .powers
+                .into_iter()
+                .map(|(base, exponent)| (base, -exponent))
+                .collect(),
+        }
+    }
+
+    fn pow(self, exponent: i64) -> Self {
+        let coefficient = match i32::try_from(exponent) {
+            Ok(exponent) if self.coefficient != 0.into() || exponent > 0 => {
+                self.coefficient.pow(exponent)
+            }
+            _ => self.coefficient,
+        };
+        Monomial {
+            coefficient,
+            powers: self
+                .powers
+                .into_iter()
+                .map(|(base, power)| (base, power * exponent))
+                .collect(),
+        }
+    }
+
+    fn is_one(&self) -> bool {
+        self.coefficient == 1.into() && self.powers.is_empty()
+    }
+
+    /// The monomial as a fraction with the negative powers in the denominator
+    fn into_equation(self) -> Equation {
+        let mut numerator = Vec::new();
+        let mut denominator = Vec::new();
+        if *self.coefficient.numer() != 1 {
+            numerator.push(Equation::from(*self.coefficient.numer()));
+        }
+        if *self.coefficient.denom() != 1 {
+            denominator.push(Equation::from(*self.coefficient.denom()));
+        }
+        for (base, exponent) in self.powers {
+            let (factors, exponent) = if exponent > 0 {
+                (&mut numerator, exponent)
+            } else {
+                (&mut denominator, -exponent)
+            };
+            factors.push(if exponent == 1 {
+                base
+            } else {
+                base.pow(exponent)
+            });
+        }
+        if denominator.is_empty() {
+            product(numerator)
+        } else {
+            product(numerator) / product(denominator)
+        }
+    }
+}
+
+fn product(mut factors: Vec<Equation>) -> Equation {
+    match factors.len() {
+        0 => Equation::from(1),
+        1 => factors.remove(0),
+        _ => Equation::Multiplication(factors),
+    }
+}
+
+fn sum(mut terms: Vec<Equation>) -> Equation {
+    match terms.len() {
+        0 => Equation::from(0),
+        1 => terms.remove(0),
+        _ => Equation::Addition(terms),
+    }
+}
+
+fn terms(equation: Equation) -> Vec<Equation> {
+    match equation {
+        Equation::Addition(terms) => terms,
+        equation => vec![equation],
+    }
+}
+
+impl Equation {
+    /// Expands the equation and groups its terms by the factors that depend on `variable`, so
+    /// `a\cdot x+b\cdot x^{2}+c\cdot x` becomes `(a+c)\cdot x+b\cdot x^{2}`
+    pub fn collect(self, variable: &Variable, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Collect terms"));
+        let mut groups: BTreeMap<Equation, Vec<Equation>> = BTreeMap::new();
+        for term in terms(self.expand(&mut None)) {
+            let monomial = Monomial::from_equation(&term);
+            let (dependent, constant): (BTreeMap<_, _>, BTreeMap<_, _>) = monomial
+                .powers
+                .into_iter()
+                .partition(|(base, _)| !base.is_constant(variable));
+            let coefficient = Monomial {
+                coefficient: monomial.coefficient,
+                powers: constant,
+            };
+            let dependent = Monomial {
+                coefficient: 1.into(),
+                powers: dependent,
+            };
+            groups
+                .entry(dependent.into_equation())
+                .or_default()
+                .push(coefficient.into_equation());
+        }
+        let collected = sum(groups
+            .into_iter()
+            .filter_map(|(dependent, coefficients)| {
+                match sum(coefficients).simplify_until_complete(&mut None) {
+                    Equation::Variable(Variable::Integer(0)) => None,
+                    Equation::Variable(Variable::Integer(1)) => Some(dependent),
+                    coefficient if dependent == Equation::from(1) => Some(coefficient),
+                    coefficient => Some(coefficient * dependent),
+                }
+            })
+            .collect());
+        close_step(step_logger, &collected);
+        collected
+    }
+}
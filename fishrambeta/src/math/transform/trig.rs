@@ -0,0 +1,270 @@
+use super::{product, sum, Monomial};
+use crate::math::steps::helpers::{close_step, open_step};
+use crate::math::steps::StepLogger;
+use crate::math::{Constant, Equation};
+use num_rational::Rational64;
+
+impl Equation {
+    /// The exact value of `\sin` or `\cos` at a rational multiple of `\pi` with a denominator
+    /// of 1, 2, 3, 4 or 6, like `\frac{1}{2}\cdot \sqrt{2}` for `\cos(\frac{\pi}{4})`
+    pub(in crate::math) fn exact_trig_value(&self) -> Option<Equation> {
+        let (argument, phase) = match self {
+            Equation::Sin(argument) => (argument, Rational64::from(0)),
+            Equation::Cos(argument) => (argument, Rational64::new(1, 2)),
+            _ => return None,
+        };
+        let turns = pi_multiple(argument)? + phase;
+        // sin is 2π periodic, odd around π and symmetric around π/2
+        let mut turns = turns - (turns / 2).floor() * 2;
+        let sign = if turns >= 1.into() {
+            turns -= 1;
+            -1
+        } else {
+            1
+        };
+        if turns > Rational64::new(1, 2) {
+            turns = Rational64::from(1) - turns;
+        }
+        let value = match (*turns.numer(), *turns.denom()) {
+            (0, _) => Equation::from(0),
+            (1, 6) => Equation::from(Rational64::new(sign, 2)),
+            (1, 4) => Equation::from(Rational64::new(sign, 2)) * Equation::from(2).sqrt(),
+            (1, 3) => Equation::from(Rational64::new(sign, 2)) * Equation::from(3).sqrt(),
+            (1, 2) => Equation::from(sign),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Rewrites `\sin` and `\cos` of sums and integer multiples with the angle sum formulas and
+    /// multiplies out the result, so `\sin(2\cdot x)` becomes `2\cdot \sin(x)\cdot \cos(x)`
+    pub fn trig_expand(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Expand trigonometric functions"));
+        let expanded = self
+            .simplify_until_complete(&mut None)
+            .trig_expanded(step_logger)
+            .expand(&mut None);
+        close_step(step_logger, &expanded);
+        expanded
+    }
+
+    fn trig_expanded(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        match self.map_children(|child| child.trig_expanded(step_logger)) {
+            Equation::Sin(argument) => expand_angle(*argument, true, step_logger),
+            Equation::Cos(argument) => expand_angle(*argument, false, step_logger),
+            equation => equation,
+        }
+    }
+
+    /// Rewrites products and powers of `\sin` and `\cos` as sums of single functions of
+    /// multiple angles, so `\sin(x)^{2}` becomes `\frac{1}{2}-\frac{1}{2}\cdot \cos(2\cdot x)`
+    /// and `\sin(x)^{2}+\cos(x)^{2}` becomes 1
+    pub fn trig_reduce(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        open_step(step_logger, &self, Some("Reduce trigonometric functions"));
+        let reduced = self
+            .simplify_until_complete(&mut None)
+            .trig_reduced(step_logger)
+            .expand(&mut None);
+        close_step(step_logger, &reduced);
+        reduced
+    }
+
+    fn trig_reduced(self, step_logger: &mut Option<StepLogger>) -> Equation {
+        match self.map_children(|child| child.trig_reduced(step_logger)) {
+            Equation::Multiplication(factors) => reduce_product(factors, step_logger),
+            Equation::Power(power) => match power.1.get_integer_or_none() {
+                Some(exponent @ 2..) if is_trig(&power.0) => {
+                    let factors = (0..exponent).map(|_| power.0.clone()).collect();
+                    reduce_product(factors, step_logger)
+                }
+                _ => Equation::Power(power),
+            },
+            equation => equation,
+        }
+    }
+}
+
+/// The rational `q` for which `argument` is `q\cdot \pi`
+fn pi_multiple(argument: &Equation) -> Option<Rational64> {
+    let monomial = Monomial::from_equation(argument);
+    let pi = Equation::from(Constant::PI);
+    match monomial.powers.iter().collect::<Vec<_>>().as_slice() {
+        [] if monomial.coefficient == 0.into() => Some(0.into()),
+        [(base, 1)] if **base == pi => Some(monomial.coefficient),
+        _ => None,
+    }
+}
+
+fn is_trig(equation: &Equation) -> bool {
+    matches!(equation, Equation::Sin(_) | Equation::Cos(_))
+}
+
+fn trig(argument: Equation, is_sin: bool) -> Equation {
+    if is_sin {
+        argument.sin()
+    } else {
+        argument.cos()
+    }
+}
+
+/// `\sin` or `\cos` of the simplified `argument`, with a negative sign taken out
+fn trig_of_positive(argument: Equation, is_sin: bool) -> Equation {
+    let argument = argument.simplify_until_complete(&mut None);
+    let monomial = Monomial::from_equation(&argument);
+    if monomial.coefficient >= 0.into() || monomial.powers.is_empty() {
+        return trig(argument, is_sin);
+    }
+    let positive = Monomial {
+        coefficient: -monomial.coefficient,
+        ..monomial
+    }
+    .into_equation();
+    if is_sin {
+        -positive.sin()
+    } else {
+        positive.cos()
+    }
+}
+
+/// Applies the angle sum formulas to `\sin` or `\cos` of `argument` until only single angles
+/// are left
+fn expand_angle(
+    argument: Equation,
+    is_sin: bool,
+    step_logger: &mut Option<StepLogger>,
+) -> Equation {
+    let (sin, cos) = expand_sin_cos(argument, is_sin, step_logger);
+    if is_sin {
+        sin
+    } else {
+        cos
+    }
+}
+
+/// The expanded `\sin` and `\cos` of `argument` together, so both are only expanded once for
+/// every angle they are built from. The steps are logged for `\sin` or `\cos` by `is_sin`.
+fn expand_sin_cos(
+    argument: Equation,
+    is_sin: bool,
+    step_logger: &mut Option<StepLogger>,
+) -> (Equation, Equation) {
+    let before = trig(argument.clone(), is_sin);
+    let monomial = Monomial::from_equation(&argument);
+    let expanded = match argument {
+        Equation::Addition(mut terms) if terms.len() > 1 => {
+            open_step(step_logger, &before, Some("Angle sum formula"));
+            let (sin_a, cos_a) = expand_sin_cos(terms.remove(0), is_sin, step_logger);
+            let (sin_b, cos_b) = expand_sin_cos(sum(terms), is_sin, step_logger);
+            (
+                sin_a.clone() * cos_b.clone() + cos_a.clone() * sin_b.clone(),
+                cos_a * cos_b - sin_a * sin_b,
+            )
+        }
+        _ if monomial.coefficient < 0.into() && !monomial.powers.is_empty() => {
+            open_step(step_logger, &before, Some("Negative angle"));
+            let positive = Monomial {
+                coefficient: -monomial.coefficient,
+                ..monomial
+            };
+            let (sin, cos) = expand_sin_cos(positive.into_equation(), is_sin, step_logger);
+            (-sin, cos)
+        }
+        _ if monomial.coefficient.is_integer()
+            && monomial.coefficient > 1.into()
+            && !monomial.powers.is_empty() =>
+        {
+            let message = if monomial.coefficient == 2.into() {
+                "Double angle formula"
+            } else {
+                "Multiple angle formula"
+            };
+            open_step(step_logger, &before, Some(message));
+            let multiple = monomial.coefficient.to_integer();
+            let single = Monomial {
+                coefficient: 1.into(),
+                ..monomial
+            };
+            let (sin_x, cos_x) = expand_sin_cos(single.into_equation(), is_sin, step_logger);
+            // Adds one angle at a time and multiplies out, so the result has a term for every
+            // power of \sin(x) instead of doubling in size with every angle
+            (1..multiple).fold((sin_x.clone(), cos_x.clone()), |(sin, cos), _| {
+                (
+                    (sin_x.clone() * cos.clone() + cos_x.clone() * sin.clone()).expand(&mut None),
+                    (cos_x.clone() * cos - sin_x.clone() * sin).expand(&mut None),
+                )
+            })
+        }
+        argument => return (argument.clone().sin(), argument.cos()),
+    };
+    close_step(step_logger, if is_sin { &expanded.0 } else { &expanded.1 });
+    expanded
+}
+
+/// Replaces pairs of `\sin` and `\cos` factors with the product to sum formulas until at most
+/// one of them is left in every term
+fn reduce_product(mut factors: Vec<Equation>, step_logger: &mut Option<StepLogger>) -> Equation {
+    let mut trig_factors = Vec::new();
+    let mut index = 0;
+    while index < factors.len() {
+        match &factors[index] {
+            equation if is_trig(equation) => trig_factors.push(factors.remove(index)),
+            Equation::Power(power) if is_trig(&power.0) => match power.1.get_integer_or_none() {
+                Some(exponent @ 1..) => {
+                    let base = power.0.clone();
+                    factors.remove(index);
+                    trig_factors.extend((0..exponent).map(|_| base.clone()));
+                }
+                _ => index += 1,
+            },
+            _ => index += 1,
+        }
+    }
+    if trig_factors.len() < 2 {
+        factors.extend(trig_factors);
+        return product(factors);
+    }
+    let (first, second) = (trig_factors.remove(0), trig_factors.remove(0));
+    let before = first.clone() * second.clone();
+    let message = if first == second {
+        "Power reduction formula"
+    } else {
+        "Product to sum formula"
+    };
+    open_step(step_logger, &before, Some(message));
+    let half = || Equation::from(Rational64::new(1, 2));
+    let reduced = match (first, second) {
+        (Equation::Sin(a), Equation::Sin(b)) => {
+            let difference = trig_of_positive(*a.clone() - *b.clone(), false);
+            half() * (difference - trig_of_positive(*a + *b, false))
+        }
+        (Equation::Cos(a), Equation::Cos(b)) => {
+            let difference = trig_of_positive(*a.clone() - *b.clone(), false);
+            half() * (difference + trig_of_positive(*a + *b, false))
+        }
+        (Equation::Sin(a), Equation::Cos(b)) | (Equation::Cos(b), Equation::Sin(a)) => {
+            let difference = trig_of_positive(*a.clone() - *b.clone(), true);
+            half() * (trig_of_positive(*a + *b, true) + difference)
+        }
+        _ => unreachable!("Only sin and cos are collected"),
+    }
+    .simplify_until_complete(&mut None);
+    close_step(step_logger, &reduced);
+    factors.extend(trig_factors);
+    factors.push(reduced);
+    // Every term of the product now has one trigonometric factor less
+    let terms = match product(factors).expand(&mut None) {
+        Equation::Addition(terms) => terms,
+        term => vec![term],
+    };
+    sum(terms
+        .into_iter()
+        .map(|term| match term {
+            Equation::Multiplication(factors) => reduce_product(factors, step_logger),
+            Equation::Power(power) if is_trig(&power.0) => {
+                reduce_product(vec![Equation::Power(power)], step_logger)
+            }
+            Equation::Negative(negative) => -negative.trig_reduced(step_logger),
+            term => term,
+        })
+        .collect())
+}
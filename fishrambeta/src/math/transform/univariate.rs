@@ -0,0 +1,261 @@
+use super::{sum, terms, Monomial};
+use crate::math::{Equation, Variable};
+use num_integer::Integer;
+use num_rational::Rational64;
+use std::ops::{Mul, Sub};
+
+/// The largest constant and leading coefficient whose divisors are tried as rational roots, as
+/// finding the divisors of larger ones takes too long
+const MAX_ROOT_SEARCH: u64 = 1_000_000_000_000;
+
+/// A polynomial in one variable with exact rational coefficients, lowest power first and
+/// without trailing zeros
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Univariate(Vec<Rational64>);
+
+impl Univariate {
+    pub(super) fn new(mut coefficients: Vec<Rational64>) -> Self {
+        while coefficients.last() == Some(&0.into()) {
+            coefficients.pop();
+        }
+        Univariate(coefficients)
+    }
+
+    /// `x - root`
+    pub(super) fn linear(root: Rational64) -> Self {
+        Univariate::new(vec![-root, 1.into()])
+    }
+
+    /// The polynomial if the equation expands to one with numbers as coefficients
+    pub(super) fn from_equation(equation: &Equation, variable: &Variable) -> Option<Self> {
+        let x = Equation::Variable(variable.clone());
+        let mut coefficients = Vec::new();
+        for term in terms(equation.clone().expand(&mut None)) {
+            let monomial = Monomial::from_equation(&term);
+            let degree = match monomial.powers.iter().collect::<Vec<_>>().as_slice() {
+                [] => 0,
+                [(base, &exponent)] if **base == x && exponent > 0 => {
+                    usize::try_from(exponent).ok()?
+                }
+                _ => return None,
+            };
+            if coefficients.len() <= degree {
+                coefficients.resize(degree + 1, 0.into());
+            }
+            coefficients[degree] += monomial.coefficient;
+        }
+        Some(Univariate::new(coefficients))
+    }
+
+    pub(super) fn to_equation(&self, variable: &Variable) -> Equation {
+        let x = Equation::Variable(variable.clone());
+        sum(self
+            .0
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, coefficient)| **coefficient != 0.into())
+            .map(|(degree, &coefficient)| {
+                let exponent = i64::try_from(degree).expect("The degree fits an i64");
+                let mut monomial = Monomial::new(coefficient);
+                if degree > 0 {
+                    monomial = monomial.with_power(x.clone(), exponent);
+                }
+                monomial.into_equation()
+            })
+            .collect())
+    }
+
+    pub(super) fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(super) fn degree(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    pub(super) fn leading(&self) -> Rational64 {
+        self.0.last().copied().unwrap_or_default()
+    }
+
+    pub(super) fn scale(&self, factor: Rational64) -> Self {
+        Univariate::new(self.0.iter().map(|c| c * factor).collect())
+    }
+
+    pub(super) fn evaluate(&self, x: Rational64) -> Rational64 {
+        self.0
+            .iter()
+            .rev()
+            .fold(Rational64::from(0), |value, &coefficient| {
+                value * x + coefficient
+            })
+    }
+
+    pub(super) fn pow(&self, exponent: usize) -> Self {
+        (0..exponent).fold(Univariate::new(vec![1.into()]), |power, _| &power * self)
+    }
+
+    /// The quotient and remainder of long division
+    ///
+    /// # Panics
+    /// Panics when dividing by zero
+    pub(super) fn div_rem(&self, divisor: &Univariate) -> (Univariate, Univariate) {
+        assert!(!divisor.is_zero(), "division by the zero polynomial");
+        let mut remainder = self.0.clone();
+        let mut quotient = vec![Rational64::from(0); self.0.len().saturating_sub(divisor.degree())];
+        while remainder.len() > divisor.degree() && !remainder.is_empty() {
+            let shift = remainder.len() - 1 - divisor.degree();
+            let factor = remainder[remainder.len() - 1] / divisor.leading();
+            quotient[shift] = factor;
+            for (i, coefficient) in divisor.0.iter().enumerate() {
+                remainder[shift + i] -= factor * coefficient;
+            }
+            remainder.pop();
+        }
+        (Univariate::new(quotient), Univariate::new(remainder))
+    }
+
+    /// `p(x + shift)`, whose coefficients are the Taylor coefficients of `p` around `shift`
+    pub(super) fn shifted(&self, shift: Rational64) -> Self {
+        let x_plus_shift = Univariate::new(vec![shift, 1.into()]);
+        self.0
+            .iter()
+            .rev()
+            .fold(Univariate::new(vec![]), |value, &coefficient| {
+                &(&value * &x_plus_shift) + coefficient
+            })
+    }
+
+    /// The first `count` coefficients of the power series of `self / divisor` around 0
+    pub(super) fn series_divide(&self, divisor: &Univariate, count: usize) -> Vec<Rational64> {
+        let coefficient = |p: &Univariate, i: usize| p.0.get(i).copied().unwrap_or_default();
+        let mut series: Vec<Rational64> = Vec::with_capacity(count);
+        for j in 0..count {
+            let known = (1..=j)
+                .map(|i| coefficient(divisor, i) * series[j - i])
+                .sum::<Rational64>();
+            series.push((coefficient(self, j) - known) / coefficient(divisor, 0));
+        }
+        series
+    }
+
+    /// The distinct rational roots, found by trying every `±p/q` with `p` dividing the constant
+    /// and `q` dividing the leading coefficient. Only zero is found when either coefficient is
+    /// larger than [`MAX_ROOT_SEARCH`].
+    pub(super) fn rational_roots(&self) -> Vec<Rational64> {
+        let denominators = self.0.iter().fold(1, |lcm, c| c.denom().lcm(&lcm));
+        let integers: Vec<i64> = self
+            .0
+            .iter()
+            .map(|c| (c * denominators).to_integer())
+            .skip_while(|&c| c == 0)
+            .collect();
+        let mut roots = Vec::new();
+        if integers.len() < self.0.len() {
+            roots.push(0.into());
+        }
+        let (Some(&constant), Some(&leading)) = (integers.first(), integers.last()) else {
+            return roots;
+        };
+        if constant.unsigned_abs() > MAX_ROOT_SEARCH || leading.unsigned_abs() > MAX_ROOT_SEARCH {
+            return roots;
+        }
+        for p in divisors(constant.unsigned_abs()) {
+            for q in divisors(leading.unsigned_abs()) {
+                for p in [p, -p] {
+                    let candidate = Rational64::new(p, q);
+                    if !roots.contains(&candidate) && is_root(&integers, p, q) {
+                        roots.push(candidate);
+                    }
+                }
+            }
+        }
+        roots
+    }
+}
+
+/// Whether `p/q` is a root of the polynomial with the integer coefficients, lowest power first,
+/// by checking that `\sum c_{i} p^{i} q^{n-i}` is zero. Candidates for which the sum overflows
+/// `i128` are treated as not being roots.
+fn is_root(coefficients: &[i64], p: i64, q: i64) -> bool {
+    let (p, q) = (i128::from(p), i128::from(q));
+    let mut value: i128 = 0;
+    let mut q_power: i128 = 1;
+    for &coefficient in coefficients.iter().rev() {
+        let Some(next) = value
+            .checked_mul(p)
+            .and_then(|value| value.checked_add(i128::from(coefficient).checked_mul(q_power)?))
+        else {
+            return false;
+        };
+        value = next;
+        let Some(next) = q_power.checked_mul(q) else {
+            return false;
+        };
+        q_power = next;
+    }
+    value == 0
+}
+
+fn divisors(n: u64) -> Vec<i64> {
+    let mut divisors = Vec::new();
+    let mut i = 1;
+    while i * i <= n {
+        if n.is_multiple_of(i) {
+            divisors.extend([i, n / i]);
+        }
+        i += 1;
+    }
+    divisors.sort_unstable();
+    divisors.dedup();
+    divisors
+        .into_iter()
+        .filter_map(|divisor| i64::try_from(divisor).ok())
+        .collect()
+}
+
+impl Mul for &Univariate {
+    type Output = Univariate;
+
+    fn mul(self, other: &Univariate) -> Univariate {
+        if self.is_zero() || other.is_zero() {
+            return Univariate::new(vec![]);
+        }
+        let mut product = vec![Rational64::from(0); self.0.len() + other.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                product[i + j] += a * b;
+            }
+        }
+        Univariate::new(product)
+    }
+}
+
+impl Sub for &Univariate {
+    type Output = Univariate;
+
+    fn sub(self, other: &Univariate) -> Univariate {
+        let length = self.0.len().max(other.0.len());
+        Univariate::new(
+            (0..length)
+                .map(|i| {
+                    self.0.get(i).copied().unwrap_or_default()
+                        - other.0.get(i).copied().unwrap_or_default()
+                })
+                .collect(),
+        )
+    }
+}
+
+impl std::ops::Add<Rational64> for &Univariate {
+    type Output = Univariate;
+
+    fn add(self, constant: Rational64) -> Univariate {
+        let mut coefficients = self.0.clone();
+        if coefficients.is_empty() {
+            coefficients.push(0.into());
+        }
+        coefficients[0] += constant;
+        Univariate::new(coefficients)
+    }
+}
@@ -0,0 +1,129 @@
+use crate::math::{Equation, Variable};
+use std::collections::BTreeSet;
+
+impl Equation {
+    /// The direct subexpressions, in the order they appear. A derivative has the differentiated
+    /// expression and the variable it is taken to.
+    pub fn children(&self) -> Vec<&Equation> {
+        match self {
+            Equation::Variable(_) => vec![],
+            Equation::Negative(e)
+            | Equation::Ln(e)
+            | Equation::Sin(e)
+            | Equation::Cos(e)
+            | Equation::Arcsin(e)
+            | Equation::Arccos(e)
+            | Equation::Arctan(e)
+            | Equation::Abs(e) => vec![e],
+            Equation::Addition(terms) | Equation::Multiplication(terms) => terms.iter().collect(),
+            Equation::Division(pair)
+            | Equation::Power(pair)
+            | Equation::Equals(pair)
+            | Equation::Derivative((pair, _)) => vec![&pair.0, &pair.1],
+        }
+    }
+
+    /// Replaces every direct subexpression with the result of `f`, keeping the variant
+    pub fn map_children(self, mut f: impl FnMut(Equation) -> Equation) -> Equation {
+        let result: Result<Equation, std::convert::Infallible> =
+            self.try_map_children(|child| Ok(f(child)));
+        match result {
+            Ok(equation) => equation,
+        }
+    }
+
+    /// Like [`Equation::map_children`], but stops at the first error of `f`
+    ///
+    /// # Errors
+    /// Returns the first error returned by `f`
+    pub fn try_map_children<E>(
+        self,
+        mut f: impl FnMut(Equation) -> Result<Equation, E>,
+    ) -> Result<Equation, E> {
+        Ok(match self {
+            Equation::Variable(v) => Equation::Variable(v),
+            Equation::Negative(e) => Equation::Negative(Box::new(f(*e)?)),
+            Equation::Ln(e) => Equation::Ln(Box::new(f(*e)?)),
+            Equation::Sin(e) => Equation::Sin(Box::new(f(*e)?)),
+            Equation::Cos(e) => Equation::Cos(Box::new(f(*e)?)),
+            Equation::Arcsin(e) => Equation::Arcsin(Box::new(f(*e)?)),
+            Equation::Arccos(e) => Equation::Arccos(Box::new(f(*e)?)),
+            Equation::Arctan(e) => Equation::Arctan(Box::new(f(*e)?)),
+            Equation::Abs(e) => Equation::Abs(Box::new(f(*e)?)),
+            Equation::Addition(terms) => {
+                Equation::Addition(terms.into_iter().map(&mut f).collect::<Result<_, _>>()?)
+            }
+            Equation::Multiplication(factors) => {
+                Equation::Multiplication(factors.into_iter().map(&mut f).collect::<Result<_, _>>()?)
+            }
+            Equation::Division(d) => Equation::Division(both(*d, &mut f)?),
+            Equation::Power(p) => Equation::Power(both(*p, &mut f)?),
+            Equation::Equals(e) => Equation::Equals(both(*e, &mut f)?),
+            Equation::Derivative((d, is_partial)) => {
+                Equation::Derivative((both(*d, &mut f)?, is_partial))
+            }
+        })
+    }
+
+    /// Every subexpression including the equation itself, parents before their children
+    pub fn iter_subexpressions(&self) -> Subexpressions<'_> {
+        Subexpressions { stack: vec![self] }
+    }
+
+    /// Folds `f` over every subexpression in the order of [`Equation::iter_subexpressions`]
+    ///
+    /// # Errors
+    /// Returns the first error returned by `f`
+    pub fn try_fold<B, E>(
+        &self,
+        init: B,
+        f: impl FnMut(B, &Equation) -> Result<B, E>,
+    ) -> Result<B, E> {
+        self.iter_subexpressions().try_fold(init, f)
+    }
+
+    /// Folds the equation bottom up, calling `f` with every subexpression and the results for its
+    /// children in the order of [`Equation::children`]
+    pub fn fold<B>(&self, f: &mut impl FnMut(&Equation, Vec<B>) -> B) -> B {
+        let children = self
+            .children()
+            .into_iter()
+            .map(|child| child.fold(f))
+            .collect();
+        f(self, children)
+    }
+
+    /// The letters and vectors the equation depends on
+    pub fn free_variables(&self) -> BTreeSet<Variable> {
+        self.iter_subexpressions()
+            .filter_map(|subexpression| match subexpression {
+                Equation::Variable(v @ (Variable::Letter(_) | Variable::Vector(_))) => {
+                    Some(v.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+fn both<E>(
+    (first, second): (Equation, Equation),
+    f: &mut impl FnMut(Equation) -> Result<Equation, E>,
+) -> Result<Box<(Equation, Equation)>, E> {
+    Ok(Box::new((f(first)?, f(second)?)))
+}
+
+/// Iterator over the subexpressions of an equation, see [`Equation::iter_subexpressions`]
+pub struct Subexpressions<'a> {
+    stack: Vec<&'a Equation>,
+}
+
+impl<'a> Iterator for Subexpressions<'a> {
+    type Item = &'a Equation;
+
+    fn next(&mut self) -> Option<&'a Equation> {
+        let equation = self.stack.pop()?;
+        self.stack.extend(equation.children().into_iter().rev());
+        Some(equation)
+    }
+}
@@ -0,0 +1,283 @@
+use super::formatters::identifier;
+use crate::math::{Constant, Equation, Variable};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const RUST_KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const C_KEYWORDS: [&str; 34] = [
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else",
+    "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef",
+    "union", "unsigned", "void", "volatile", "while",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    C,
+}
+
+/// How tightly generated code binds, to only add the brackets that are needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Sum,
+    Product,
+    Unary,
+    Atom,
+}
+
+impl Equation {
+    /// Generates a Rust function evaluating the equation with `f64` math. Subexpressions used
+    /// more than once are computed once in a `let` binding, and variable names are turned into
+    /// valid identifiers, so `m_e` stays `m_e` and `\hbar` becomes `hbar`. For an equation like
+    /// `E=mc^2` the right-hand side is evaluated.
+    pub fn to_rust_fn(&self, name: &str, parameters: &[Variable]) -> String {
+        let (statements, result) = CodeGenerator::new(Language::Rust, parameters).generate(self);
+        let mut code = format!(
+            "pub fn {}({}) -> f64 {{\n",
+            sanitize(name, Language::Rust),
+            parameter_names(parameters, Language::Rust)
+                .iter()
+                .map(|parameter| format!("{parameter}: f64"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        for (temporary, value) in statements {
+            writeln!(code, "    let {temporary} = {value};").unwrap();
+        }
+        code + &format!("    {result}\n}}\n")
+    }
+
+    /// Generates a C function evaluating the equation with `double` math from `math.h`, in the
+    /// same way as [`Equation::to_rust_fn`]
+    pub fn to_c_fn(&self, name: &str, parameters: &[Variable]) -> String {
+        let (statements, result) = CodeGenerator::new(Language::C, parameters).generate(self);
+        let mut code = format!(
+            "double {}({}) {{\n",
+            sanitize(name, Language::C),
+            parameter_names(parameters, Language::C)
+                .iter()
+                .map(|parameter| format!("double {parameter}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        for (temporary, value) in statements {
+            writeln!(code, "    const double {temporary} = {value};").unwrap();
+        }
+        code + &format!("    return {result};\n}}\n")
+    }
+}
+
+fn parameter_names(parameters: &[Variable], language: Language) -> Vec<String> {
+    parameters
+        .iter()
+        .map(|parameter| match parameter {
+            Variable::Letter(name) | Variable::Vector(name) => sanitize(name, language),
+            _ => panic!("Parameters must be letters or vectors, found {parameter:?}"),
+        })
+        .collect()
+}
+
+/// Turns a variable name into an ASCII identifier that isn't a keyword
+fn sanitize(name: &str, language: Language) -> String {
+    let mut sanitized: String = identifier(name)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    let keywords: &[&str] = match language {
+        Language::Rust => &RUST_KEYWORDS,
+        Language::C => &C_KEYWORDS,
+    };
+    if keywords.contains(&sanitized.as_str()) {
+        sanitized.push('_');
+    }
+    sanitized
+}
+
+struct CodeGenerator<'a> {
+    language: Language,
+    /// How often each subexpression occurs, not counting the insides of repeated occurrences
+    counts: HashMap<&'a Equation, usize>,
+    temporaries: HashMap<&'a Equation, String>,
+    statements: Vec<(String, String)>,
+    reserved_names: Vec<String>,
+}
+
+impl<'a> CodeGenerator<'a> {
+    fn new(language: Language, parameters: &[Variable]) -> Self {
+        CodeGenerator {
+            language,
+            counts: HashMap::new(),
+            temporaries: HashMap::new(),
+            statements: vec![],
+            reserved_names: parameter_names(parameters, language),
+        }
+    }
+
+    /// Returns the hoisted subexpressions as name and value, and the final expression
+    fn generate(mut self, equation: &'a Equation) -> (Vec<(String, String)>, String) {
+        let equation = match equation {
+            Equation::Equals(equals) => &equals.1,
+            equation => equation,
+        };
+        self.count(equation);
+        let (result, _) = self.expression(equation);
+        (self.statements, result)
+    }
+
+    fn count(&mut self, equation: &'a Equation) {
+        let count = self.counts.entry(equation).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            return;
+        }
+        for child in equation.children() {
+            self.count(child);
+        }
+    }
+
+    fn expression(&mut self, equation: &'a Equation) -> (String, Precedence) {
+        if let Some(temporary) = self.temporaries.get(equation) {
+            return (temporary.clone(), Precedence::Atom);
+        }
+        let (code, precedence) = self.uncached_expression(equation);
+        if matches!(equation, Equation::Variable(_)) || self.counts[equation] < 2 {
+            return (code, precedence);
+        }
+        let temporary = self.temporary_name();
+        self.statements.push((temporary.clone(), code));
+        self.temporaries.insert(equation, temporary.clone());
+        (temporary, Precedence::Atom)
+    }
+
+    fn temporary_name(&self) -> String {
+        // One more candidate than there are names in use, so one of them is free
+        (0..=self.statements.len() + self.reserved_names.len())
+            .map(|index| format!("t{index}"))
+            .find(|name| {
+                !self.reserved_names.contains(name)
+                    && !self
+                        .statements
+                        .iter()
+                        .any(|(temporary, _)| temporary == name)
+            })
+            .expect("There are more candidates than names in use")
+    }
+
+    /// The expression for `equation` with brackets if it binds looser than `minimum`
+    fn operand(&mut self, equation: &'a Equation, minimum: Precedence) -> String {
+        let (code, precedence) = self.expression(equation);
+        if precedence < minimum {
+            format!("({code})")
+        } else {
+            code
+        }
+    }
+
+    fn call(&mut self, function: &str, arguments: &[&'a Equation]) -> (String, Precedence) {
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.expression(argument).0)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let function = match self.language {
+            Language::Rust => format!("f64::{function}"),
+            Language::C => function.to_string(),
+        };
+        (format!("{function}({arguments})"), Precedence::Atom)
+    }
+
+    fn uncached_expression(&mut self, equation: &'a Equation) -> (String, Precedence) {
+        let rust = self.language == Language::Rust;
+        match equation {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) if *i < 0 => (format!("{i}.0"), Precedence::Unary),
+                Variable::Integer(i) => (format!("{i}.0"), Precedence::Atom),
+                Variable::Rational(r) => (
+                    format!("{}.0 / {}.0", r.numer(), r.denom()),
+                    Precedence::Product,
+                ),
+                Variable::Constant(c) => {
+                    let constant = match (c, self.language) {
+                        (Constant::PI, Language::Rust) => "std::f64::consts::PI",
+                        (Constant::E, Language::Rust) => "std::f64::consts::E",
+                        (Constant::PI, Language::C) => "M_PI",
+                        (Constant::E, Language::C) => "M_E",
+                    };
+                    (constant.to_string(), Precedence::Atom)
+                }
+                Variable::Letter(l) | Variable::Vector(l) => {
+                    (sanitize(l, self.language), Precedence::Atom)
+                }
+            },
+            Equation::Negative(n) => (
+                format!("-{}", self.operand(n, Precedence::Atom)),
+                Precedence::Unary,
+            ),
+            Equation::Addition(a) => (
+                a.iter()
+                    .map(|t| self.operand(t, Precedence::Sum))
+                    .collect::<Vec<_>>()
+                    .join(" + "),
+                Precedence::Sum,
+            ),
+            Equation::Multiplication(m) => (
+                m.iter()
+                    .map(|t| self.operand(t, Precedence::Product))
+                    .collect::<Vec<_>>()
+                    .join(" * "),
+                Precedence::Product,
+            ),
+            Equation::Division(d) => (
+                format!(
+                    "{} / {}",
+                    self.operand(&d.0, Precedence::Product),
+                    self.operand(&d.1, Precedence::Unary)
+                ),
+                Precedence::Product,
+            ),
+            Equation::Power(p) => match &**p {
+                (Equation::Variable(Variable::Constant(Constant::E)), exponent) => {
+                    self.call("exp", &[exponent])
+                }
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() == 2 =>
+                {
+                    self.call("sqrt", &[base])
+                }
+                (base, Equation::Variable(Variable::Integer(exponent))) if rust => {
+                    match i32::try_from(*exponent) {
+                        Ok(exponent) => (
+                            format!("f64::powi({}, {exponent})", self.expression(base).0),
+                            Precedence::Atom,
+                        ),
+                        Err(_) => self.call("powf", &[base, &p.1]),
+                    }
+                }
+                (base, exponent) if rust => self.call("powf", &[base, exponent]),
+                (base, exponent) => self.call("pow", &[base, exponent]),
+            },
+            Equation::Ln(l) if rust => self.call("ln", &[l]),
+            Equation::Ln(l) => self.call("log", &[l]),
+            Equation::Sin(s) => self.call("sin", &[s]),
+            Equation::Cos(c) => self.call("cos", &[c]),
+            Equation::Arcsin(s) => self.call("asin", &[s]),
+            Equation::Arccos(c) => self.call("acos", &[c]),
+            Equation::Arctan(t) => self.call("atan", &[t]),
+            Equation::Abs(a) if rust => self.call("abs", &[a]),
+            Equation::Abs(a) => self.call("fabs", &[a]),
+            Equation::Equals(_) => panic!("Only a top level equals can be compiled"),
+            Equation::Derivative(_) => panic!("Derivative cannot be compiled"),
+        }
+    }
+}
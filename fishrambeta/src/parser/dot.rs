@@ -0,0 +1,109 @@
+use crate::math::steps::Step;
+use crate::math::{Constant, Equation, Variable};
+use std::fmt::Write;
+
+/// Writes the nodes and edges of expression trees, numbering the nodes as they are written
+struct DotWriter {
+    body: String,
+    nodes: usize,
+    indent: &'static str,
+}
+
+impl DotWriter {
+    fn new(indent: &'static str) -> Self {
+        DotWriter {
+            body: String::new(),
+            nodes: 0,
+            indent,
+        }
+    }
+
+    /// Writes the tree of `equation` and returns the name of its root node
+    fn tree(&mut self, equation: &Equation, prefix: &str) -> String {
+        let name = format!("{prefix}{}", self.nodes);
+        self.nodes += 1;
+        writeln!(
+            self.body,
+            "{}{name} [label=\"{}\"];",
+            self.indent,
+            escape(&label(equation))
+        )
+        .unwrap();
+        // `ordering=out` keeps the children in the order they appear
+        for child in equation.children() {
+            let child = self.tree(child, prefix);
+            writeln!(self.body, "{}{name} -> {child};", self.indent).unwrap();
+        }
+        name
+    }
+}
+
+fn label(equation: &Equation) -> String {
+    match equation {
+        Equation::Variable(v) => match v {
+            Variable::Integer(i) => format!("Integer {i}"),
+            Variable::Rational(r) => format!("Rational {r}"),
+            Variable::Constant(Constant::PI) => "Constant π".to_string(),
+            Variable::Constant(Constant::E) => "Constant e".to_string(),
+            Variable::Letter(l) => format!("Letter {l}"),
+            Variable::Vector(v) => format!("Vector {v}"),
+        },
+        Equation::Negative(_) => "Negative".to_string(),
+        Equation::Addition(_) => "Addition".to_string(),
+        Equation::Multiplication(_) => "Multiplication".to_string(),
+        Equation::Division(_) => "Division".to_string(),
+        Equation::Power(_) => "Power".to_string(),
+        Equation::Ln(_) => "Ln".to_string(),
+        Equation::Equals(_) => "Equals".to_string(),
+        Equation::Sin(_) => "Sin".to_string(),
+        Equation::Cos(_) => "Cos".to_string(),
+        Equation::Arcsin(_) => "Arcsin".to_string(),
+        Equation::Arccos(_) => "Arccos".to_string(),
+        Equation::Arctan(_) => "Arctan".to_string(),
+        Equation::Abs(_) => "Abs".to_string(),
+        Equation::Derivative((_, true)) => "Partial derivative".to_string(),
+        Equation::Derivative((_, false)) => "Derivative".to_string(),
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl Equation {
+    /// Emits a Graphviz digraph of the expression tree with one node per subexpression, to debug
+    /// simplifications with `dot -Tsvg`
+    pub fn to_dot(&self) -> String {
+        let mut writer = DotWriter::new("    ");
+        writer.tree(self, "n");
+        format!(
+            "digraph equation {{\n    ordering=out;\n    node [shape=box];\n{}}}\n",
+            writer.body
+        )
+    }
+}
+
+impl Step {
+    /// Emits a Graphviz digraph with the trees before and after the step side by side, labelled
+    /// with the message of the step
+    pub fn to_dot(&self) -> String {
+        let mut dot = "digraph step {\n    ordering=out;\n    node [shape=box];\n".to_string();
+        if let Some(message) = self.message() {
+            writeln!(dot, "    label=\"{}\";", escape(message)).unwrap();
+        }
+        for (cluster, title, equation) in [
+            ("before", "Before", self.equation_before()),
+            ("after", "After", self.equation_after()),
+        ] {
+            let mut writer = DotWriter::new("        ");
+            writer.tree(equation, cluster);
+            write!(
+                dot,
+                "    subgraph cluster_{cluster} {{\n        label=\"{title}\";\n{}    }}\n",
+                writer.body
+            )
+            .unwrap();
+        }
+        dot + "}\n"
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+use std::ops::Range;
+
+/// An error encountered while parsing, with the byte range of the offending part of the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Range<usize>,
+}
+
+/// The different ways parsing can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    EmptyInput,
+    MissingOperand,
+    UnclosedBracket(char),
+    UnexpectedClosingBracket(char),
+    MismatchedBracket {
+        expected: char,
+        found: char,
+    },
+    WrongArgumentCount {
+        command: String,
+        expected: usize,
+        found: usize,
+    },
+    InvalidNumber(String),
+    UnexpectedToken(String),
+    InvalidMathml(String),
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, span: Range<usize>) -> Self {
+        ParseError { kind, span }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.span.start)
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::EmptyInput => write!(f, "input is empty"),
+            ParseErrorKind::MissingOperand => write!(f, "expected an expression"),
+            ParseErrorKind::UnclosedBracket(c) => write!(f, "bracket '{c}' is never closed"),
+            ParseErrorKind::UnexpectedClosingBracket(c) => {
+                write!(f, "closing bracket '{c}' has no matching opening bracket")
+            }
+            ParseErrorKind::MismatchedBracket { expected, found } => {
+                write!(f, "expected closing bracket '{expected}', found '{found}'")
+            }
+            ParseErrorKind::WrongArgumentCount {
+                command,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{command} expects {expected} bracketed argument(s), found {found}"
+            ),
+            ParseErrorKind::InvalidNumber(number) => write!(f, "invalid number '{number}'"),
+            ParseErrorKind::UnexpectedToken(token) => write!(f, "unexpected '{token}'"),
+            ParseErrorKind::InvalidMathml(reason) => write!(f, "invalid MathML: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
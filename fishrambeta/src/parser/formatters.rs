@@ -1,68 +1,205 @@
+use super::mathml::SYMBOLS;
 use crate::math::{Constant, Equation, Variable};
+use num::Rational64;
+
+/// Options for [`Equation::to_latex_with_options`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatexOptions {
+    /// Print the numeric factors of a product before the other factors
+    pub coefficients_first: bool,
+}
+
+/// How tightly printed LaTeX binds, to decide where brackets are needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum LatexPrecedence {
+    Equals,
+    Sum,
+    Product,
+    Power,
+    Atom,
+}
 
 impl Equation {
     pub fn to_latex(&self) -> String {
-        return match self {
+        self.to_latex_with_options(LatexOptions::default())
+    }
+
+    /// Formats the equation as LaTeX with only the brackets that are needed. Subtraction is
+    /// printed as `a-b` and negative powers as fractions.
+    pub fn to_latex_with_options(&self, options: LatexOptions) -> String {
+        let latex = |equation: &Equation| equation.to_latex_with_options(options);
+        match self {
             Equation::Variable(v) => match v {
-                Variable::Integer(i) => return i.to_string(),
-                Variable::Rational(r) => {
-                    return format!("\\frac{{{}}}{{{}}}", r.numer(), r.denom())
+                Variable::Integer(i) => i.to_string(),
+                Variable::Rational(r) if *r.numer() < 0 => {
+                    format!("-\\frac{{{}}}{{{}}}", r.numer().unsigned_abs(), r.denom())
                 }
+                Variable::Rational(r) => format!("\\frac{{{}}}{{{}}}", r.numer(), r.denom()),
                 Variable::Constant(c) => match c {
                     Constant::PI => "\\pi".to_string(),
                     Constant::E => "e".to_string(),
                 },
-                Variable::Letter(l) => return l.to_string(),
-                Variable::Vector(_) => todo!(),
+                Variable::Letter(l) => l.clone(),
+                Variable::Vector(v) => format!("\\vec{{{v}}}"),
             },
-            Equation::Negative(n) => {
-                if n.needs_to_be_bracketet() {
-                    format!("-({})", n.to_latex())
-                } else {
-                    format!("-{}", n.to_latex())
+            Equation::Negative(n) => format!(
+                "-{}",
+                n.to_latex_bracketed(options, n.latex_precedence(options) <= LatexPrecedence::Sum)
+            ),
+            Equation::Addition(a) => {
+                let mut sum = String::new();
+                for (i, term) in a.iter().enumerate() {
+                    match term.latex_negated(options) {
+                        Some(negated) if i > 0 => {
+                            sum.push('-');
+                            sum += &negated.to_latex_bracketed(
+                                options,
+                                negated.latex_precedence(options) <= LatexPrecedence::Sum,
+                            );
+                        }
+                        _ => {
+                            if i > 0 {
+                                sum.push('+');
+                            }
+                            let is_nested =
+                                matches!(term, Equation::Addition(terms) if terms.len() > 1);
+                            sum += &term.to_latex_bracketed(
+                                options,
+                                is_nested || matches!(term, Equation::Equals(_)),
+                            );
+                        }
+                    }
                 }
+                sum
             }
-            Equation::Addition(a) => a
-                .iter()
-                .map(|t| {
-                    if t.needs_to_be_bracketet() {
-                        format!("({})", t.to_latex())
-                    } else {
-                        t.to_latex()
-                    }
+            Equation::Multiplication(m) => latex_factors(m, options)
+                .into_iter()
+                .enumerate()
+                .map(|(i, factor)| {
+                    let needs_brackets = match factor {
+                        Equation::Addition(terms) | Equation::Multiplication(terms) => {
+                            terms.len() > 1
+                        }
+                        Equation::Equals(_) => true,
+                        _ => i > 0 && factor.latex_negated(options).is_some(),
+                    };
+                    (factor, needs_brackets)
                 })
+                .map(|(factor, needs_brackets)| factor.to_latex_bracketed(options, needs_brackets))
                 .collect::<Vec<_>>()
-                .join("+"),
-            Equation::Multiplication(m) => m
-                .iter()
-                .map(|t| {
-                    if t.needs_to_be_bracketet() {
-                        format!("({})", t.to_latex())
-                    } else {
-                        t.to_latex()
+                .join("\\cdot "),
+            Equation::Division(d) => match &**d {
+                (Equation::Ln(argument), Equation::Ln(base)) => {
+                    format!("\\log_{{{}}}({})", latex(base), latex(argument))
+                }
+                (numerator, denominator) => {
+                    format!("\\frac{{{}}}{{{}}}", latex(numerator), latex(denominator))
+                }
+            },
+            Equation::Power(p) => match &**p {
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() == 2 =>
+                {
+                    format!("\\sqrt{{{}}}", latex(base))
+                }
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() > 2 =>
+                {
+                    format!("\\sqrt[{}]{{{}}}", r.denom(), latex(base))
+                }
+                (base, exponent) => match exponent.latex_negated(options) {
+                    Some(Equation::Variable(Variable::Integer(1))) => {
+                        format!("\\frac{{1}}{{{}}}", latex(base))
                     }
+                    Some(exponent) => format!(
+                        "\\frac{{1}}{{{}}}",
+                        latex(&Equation::Power(Box::new((base.clone(), exponent))))
+                    ),
+                    None => format!(
+                        "{}^{{{}}}",
+                        base.to_latex_bracketed(
+                            options,
+                            base.latex_precedence(options) < LatexPrecedence::Atom
+                        ),
+                        latex(exponent)
+                    ),
+                },
+            },
+            Equation::Ln(l) => format!("\\ln({})", latex(l)),
+            Equation::Equals(e) => format!("{}={}", latex(&e.0), latex(&e.1)),
+            Equation::Sin(s) => format!("\\sin({})", latex(s)),
+            Equation::Cos(c) => format!("\\cos({})", latex(c)),
+            Equation::Arcsin(s) => format!("\\arcsin({})", latex(s)),
+            Equation::Arccos(c) => format!("\\arccos({})", latex(c)),
+            Equation::Arctan(t) => format!("\\arctan({})", latex(t)),
+            Equation::Abs(a) => format!("|{}|", latex(a)),
+            Equation::Derivative((d, true)) => {
+                format!(
+                    "\\frac{{\\partial}}{{\\partial {}}}({})",
+                    latex(&d.1),
+                    latex(&d.0)
+                )
+            }
+            Equation::Derivative((d, false)) => {
+                format!("\\frac{{d}}{{d{}}}({})", latex(&d.1), latex(&d.0))
+            }
+        }
+    }
+
+    fn to_latex_bracketed(&self, options: LatexOptions, needs_brackets: bool) -> String {
+        if needs_brackets {
+            format!("({})", self.to_latex_with_options(options))
+        } else {
+            self.to_latex_with_options(options)
+        }
+    }
+
+    pub(super) fn latex_precedence(&self, options: LatexOptions) -> LatexPrecedence {
+        match self {
+            Equation::Addition(terms) | Equation::Multiplication(terms) if terms.len() == 1 => {
+                terms[0].latex_precedence(options)
+            }
+            _ if self.latex_negated(options).is_some() => LatexPrecedence::Sum,
+            Equation::Equals(_) => LatexPrecedence::Equals,
+            Equation::Addition(terms) if terms.len() > 1 => LatexPrecedence::Sum,
+            Equation::Multiplication(factors) if factors.len() > 1 => LatexPrecedence::Product,
+            Equation::Division(_) | Equation::Variable(Variable::Rational(_)) => {
+                LatexPrecedence::Product
+            }
+            Equation::Power(_) => LatexPrecedence::Power,
+            _ => LatexPrecedence::Atom,
+        }
+    }
+
+    /// If the equation is printed with a leading minus sign, the equation that is printed after it
+    pub(super) fn latex_negated(&self, options: LatexOptions) -> Option<Equation> {
+        match self {
+            Equation::Negative(n) => Some((**n).clone()),
+            Equation::Variable(Variable::Integer(i)) if *i < 0 => {
+                Some(Equation::Variable(Variable::Integer(i.checked_neg()?)))
+            }
+            Equation::Variable(Variable::Rational(r)) if *r.numer() < 0 => {
+                Some(Equation::Variable(Variable::Rational(Rational64::new(
+                    r.numer().checked_neg()?,
+                    *r.denom(),
+                ))))
+            }
+            Equation::Multiplication(factors) if factors.len() > 1 => {
+                let mut factors = latex_factors(factors, options)
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                factors[0] = factors[0].latex_negated(options)?;
+                if factors[0] == Equation::Variable(Variable::Integer(1)) {
+                    factors.remove(0);
+                }
+                Some(match factors.len() {
+                    1 => factors.remove(0),
+                    _ => Equation::Multiplication(factors),
                 })
-                .collect::<Vec<_>>()
-                .join("\\cdot "),
-            Equation::Division(d) => format!("\\frac{{{}}}{{{}}}", d.0, d.1),
-            Equation::Power(p) => {
-                let base = if p.0.needs_to_be_bracketet() {
-                    format!("({})", p.0.to_latex())
-                } else {
-                    p.0.to_latex()
-                };
-                format!("{}^{{{}}}", base, p.1.to_latex())
-            }
-            Equation::Ln(l) => format!("\\ln({l})"),
-            Equation::Equals(e) => format!("{}={}", e.0, e.1),
-            Equation::Sin(s) => format!("\\sin({s})"),
-            Equation::Cos(c) => format!("\\cos({c})"),
-            Equation::Arcsin(s) => format!("\\arcsin({s})"),
-            Equation::Arccos(c) => format!("\\arccos({c})"),
-            Equation::Arctan(t) => format!("\\arctan({t})"),
-            Equation::Abs(a) => format!("|{a}|"),
-            Equation::Derivative(_) => todo!(),
-        };
+            }
+            _ => None,
+        }
     }
 
     fn needs_to_be_bracketet(&self) -> bool {
@@ -85,40 +222,489 @@ impl Equation {
         }
     }
 
+    /// Formats the equation as a numpy expression. Derivatives are taken numerically with
+    /// `np.gradient`, so the variable has to be an array of sample points.
     pub fn to_numpy(&self) -> String {
-        return match self {
+        self.fold(&mut |equation, children: Vec<String>| match equation {
             Equation::Variable(v) => match v {
-                Variable::Integer(i) => return i.to_string(),
-                Variable::Rational(r) => return format!("({})/({})", r.numer(), r.denom()),
+                Variable::Integer(i) => i.to_string(),
+                Variable::Rational(r) => format!("({})/({})", r.numer(), r.denom()),
                 Variable::Constant(c) => match c {
                     Constant::PI => "np.pi".to_string(),
                     Constant::E => "np.e".to_string(),
                 },
-                Variable::Letter(l) => return l.to_string(),
-                Variable::Vector(_) => todo!(),
+                Variable::Letter(l) | Variable::Vector(l) => identifier(l),
             },
-            Equation::Negative(n) => format!("-({})", n.to_numpy()),
-            Equation::Addition(a) => a
-                .iter()
-                .map(|t| format!("({})", t.to_numpy()))
-                .collect::<Vec<_>>()
-                .join("+"),
+            Equation::Negative(_) => format!("-({})", children[0]),
+            Equation::Addition(_) => infix(&children, "+"),
+            Equation::Multiplication(_) => infix(&children, "*"),
+            Equation::Division(_) => infix(&children, "/"),
+            Equation::Power(p) if matches!(p.1, Equation::Variable(Variable::Integer(_))) => {
+                infix(&children, "**")
+            }
+            Equation::Power(_) => format!("np.power(({}),({}))", children[0], children[1]),
+            Equation::Ln(_) => format!("np.log({})", children[0]),
+            Equation::Equals(_) => format!("{}={}", children[0], children[1]),
+            Equation::Sin(_) => format!("np.sin({})", children[0]),
+            Equation::Cos(_) => format!("np.cos({})", children[0]),
+            Equation::Arcsin(_) => format!("np.arcsin({})", children[0]),
+            Equation::Arccos(_) => format!("np.arccos({})", children[0]),
+            Equation::Arctan(_) => format!("np.arctan({})", children[0]),
+            Equation::Abs(_) => format!("np.abs({})", children[0]),
+            Equation::Derivative(_) => format!("np.gradient({}, {})", children[0], children[1]),
+        })
+    }
+
+    /// Formats the equation as a SymPy expression, with exact integers and rationals
+    pub fn to_sympy(&self) -> String {
+        self.fold(&mut |equation, children: Vec<String>| match equation {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) => format!("sp.Integer({i})"),
+                Variable::Rational(r) => format!("sp.Rational({}, {})", r.numer(), r.denom()),
+                Variable::Constant(c) => match c {
+                    Constant::PI => "sp.pi".to_string(),
+                    Constant::E => "sp.E".to_string(),
+                },
+                Variable::Letter(l) => format!("sp.Symbol(\"{}\")", identifier(l)),
+                Variable::Vector(v) => {
+                    format!("sp.Symbol(\"{}\", commutative=False)", identifier(v))
+                }
+            },
+            Equation::Negative(_) => format!("-({})", children[0]),
+            Equation::Addition(_) => infix(&children, "+"),
+            Equation::Multiplication(_) => infix(&children, "*"),
+            Equation::Division(_) => infix(&children, "/"),
+            Equation::Power(_) => infix(&children, "**"),
+            Equation::Ln(_) => format!("sp.log({})", children[0]),
+            Equation::Equals(_) => format!("sp.Eq({}, {})", children[0], children[1]),
+            Equation::Sin(_) => format!("sp.sin({})", children[0]),
+            Equation::Cos(_) => format!("sp.cos({})", children[0]),
+            Equation::Arcsin(_) => format!("sp.asin({})", children[0]),
+            Equation::Arccos(_) => format!("sp.acos({})", children[0]),
+            Equation::Arctan(_) => format!("sp.atan({})", children[0]),
+            Equation::Abs(_) => format!("sp.Abs({})", children[0]),
+            Equation::Derivative(_) => {
+                format!("sp.Derivative({}, {})", children[0], children[1])
+            }
+        })
+    }
+
+    /// Formats the equation in the Wolfram Language, writing subscripted names as `Subscript`.
+    /// Names are the same identifiers the other code formatters use, except that `epsilon_0`
+    /// becomes `Subscript[epsilon, 0]`, as an underscore in a Wolfram symbol starts a pattern.
+    pub fn to_wolfram(&self) -> String {
+        self.fold(&mut |equation, children: Vec<String>| match equation {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) => i.to_string(),
+                Variable::Rational(r) => format!("({})/({})", r.numer(), r.denom()),
+                Variable::Constant(c) => match c {
+                    Constant::PI => "Pi".to_string(),
+                    Constant::E => "E".to_string(),
+                },
+                Variable::Letter(l) => wolfram_name(l),
+                Variable::Vector(v) => format!("OverVector[{}]", wolfram_name(v)),
+            },
+            Equation::Negative(_) => format!("-({})", children[0]),
+            Equation::Addition(_) => infix(&children, "+"),
+            Equation::Multiplication(_) => infix(&children, "*"),
+            Equation::Division(_) => infix(&children, "/"),
+            Equation::Power(_) => infix(&children, "^"),
+            Equation::Ln(_) => format!("Log[{}]", children[0]),
+            Equation::Equals(_) => format!("{} == {}", children[0], children[1]),
+            Equation::Sin(_) => format!("Sin[{}]", children[0]),
+            Equation::Cos(_) => format!("Cos[{}]", children[0]),
+            Equation::Arcsin(_) => format!("ArcSin[{}]", children[0]),
+            Equation::Arccos(_) => format!("ArcCos[{}]", children[0]),
+            Equation::Arctan(_) => format!("ArcTan[{}]", children[0]),
+            Equation::Abs(_) => format!("Abs[{}]", children[0]),
+            Equation::Derivative(_) => format!("D[{}, {}]", children[0], children[1]),
+        })
+    }
+
+    /// Formats the equation as Julia code, with exact rationals. Derivatives use `ForwardDiff`.
+    pub fn to_julia(&self) -> String {
+        self.fold(&mut |equation, children: Vec<String>| match equation {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) => i.to_string(),
+                Variable::Rational(r) => format!("({}//{})", r.numer(), r.denom()),
+                Variable::Constant(c) => match c {
+                    Constant::PI => "pi".to_string(),
+                    Constant::E => "ℯ".to_string(),
+                },
+                Variable::Letter(l) | Variable::Vector(l) => identifier(l),
+            },
+            Equation::Negative(_) => format!("-({})", children[0]),
+            Equation::Addition(_) => infix(&children, "+"),
+            Equation::Multiplication(_) => infix(&children, "*"),
+            Equation::Division(_) => infix(&children, "/"),
+            Equation::Power(_) => infix(&children, "^"),
+            Equation::Ln(_) => format!("log({})", children[0]),
+            Equation::Equals(_) => format!("{} == {}", children[0], children[1]),
+            Equation::Sin(_) => format!("sin({})", children[0]),
+            Equation::Cos(_) => format!("cos({})", children[0]),
+            Equation::Arcsin(_) => format!("asin({})", children[0]),
+            Equation::Arccos(_) => format!("acos({})", children[0]),
+            Equation::Arctan(_) => format!("atan({})", children[0]),
+            Equation::Abs(_) => format!("abs({})", children[0]),
+            Equation::Derivative(_) => {
+                let (operand, variable) = (&children[0], &children[1]);
+                format!("ForwardDiff.derivative({variable} -> {operand}, {variable})")
+            }
+        })
+    }
+
+    /// Formats the equation as Typst math, to be placed between `$` signs
+    pub fn to_typst(&self) -> String {
+        let options = LatexOptions::default();
+        match self {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) => i.to_string(),
+                Variable::Rational(r) if *r.numer() < 0 => {
+                    format!("-frac({}, {})", r.numer().unsigned_abs(), r.denom())
+                }
+                Variable::Rational(r) => format!("frac({}, {})", r.numer(), r.denom()),
+                Variable::Constant(c) => match c {
+                    Constant::PI => "pi".to_string(),
+                    Constant::E => "e".to_string(),
+                },
+                Variable::Letter(l) => typst_name(l),
+                Variable::Vector(v) => format!("arrow({})", typst_name(v)),
+            },
+            Equation::Negative(n) => format!(
+                "-{}",
+                n.to_typst_bracketed(n.latex_precedence(options) <= LatexPrecedence::Sum)
+            ),
+            Equation::Addition(a) => {
+                let mut sum = String::new();
+                for (i, term) in a.iter().enumerate() {
+                    match term.latex_negated(options) {
+                        Some(negated) if i > 0 => {
+                            sum += " - ";
+                            sum += &negated.to_typst_bracketed(
+                                negated.latex_precedence(options) <= LatexPrecedence::Sum,
+                            );
+                        }
+                        _ => {
+                            if i > 0 {
+                                sum += " + ";
+                            }
+                            let is_nested =
+                                matches!(term, Equation::Addition(terms) if terms.len() > 1);
+                            sum += &term.to_typst_bracketed(
+                                is_nested || matches!(term, Equation::Equals(_)),
+                            );
+                        }
+                    }
+                }
+                sum
+            }
             Equation::Multiplication(m) => m
                 .iter()
-                .map(|t| format!("({})", t.to_numpy()))
+                .enumerate()
+                .map(|(i, factor)| {
+                    let needs_brackets = match factor {
+                        Equation::Addition(terms) | Equation::Multiplication(terms) => {
+                            terms.len() > 1
+                        }
+                        Equation::Equals(_) => true,
+                        _ => i > 0 && factor.latex_negated(options).is_some(),
+                    };
+                    factor.to_typst_bracketed(needs_brackets)
+                })
                 .collect::<Vec<_>>()
-                .join("*"),
-            Equation::Division(d) => format!("({})/({})", d.0.to_numpy(), d.1.to_numpy()),
-            Equation::Power(p) => format!("np.power(({}),({}))", p.0.to_numpy(), p.1.to_numpy()),
-            Equation::Ln(l) => format!("np.log({})", l.to_numpy()),
-            Equation::Equals(e) => format!("{}={}", e.0.to_numpy(), e.1.to_numpy()),
-            Equation::Sin(s) => format!("np.sin({})", s.to_numpy()),
-            Equation::Cos(c) => format!("np.cos({})", c.to_numpy()),
-            Equation::Arcsin(s) => format!("np.arcsin({})", s.to_numpy()),
-            Equation::Arccos(c) => format!("np.arccos({})", c.to_numpy()),
-            Equation::Arctan(t) => format!("np.arctan({})", t.to_numpy()),
-            Equation::Abs(a) => format!("np.abs({})", a.to_numpy()),
-            Equation::Derivative(_) => todo!(),
-        };
+                .join(" dot "),
+            Equation::Division(d) => match &**d {
+                (Equation::Ln(argument), Equation::Ln(base)) => {
+                    format!("log_({})({})", base.to_typst(), argument.to_typst())
+                }
+                (numerator, denominator) => {
+                    format!("frac({}, {})", numerator.to_typst(), denominator.to_typst())
+                }
+            },
+            Equation::Power(p) => match &**p {
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() == 2 =>
+                {
+                    format!("sqrt({})", base.to_typst())
+                }
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() > 2 =>
+                {
+                    format!("root({}, {})", r.denom(), base.to_typst())
+                }
+                (base, exponent) => match exponent.latex_negated(options) {
+                    Some(Equation::Variable(Variable::Integer(1))) => {
+                        format!("frac(1, {})", base.to_typst())
+                    }
+                    Some(exponent) => format!(
+                        "frac(1, {})",
+                        Equation::Power(Box::new((base.clone(), exponent))).to_typst()
+                    ),
+                    None => format!(
+                        "{}^({})",
+                        base.to_typst_bracketed(
+                            base.latex_precedence(options) < LatexPrecedence::Atom
+                        ),
+                        exponent.to_typst()
+                    ),
+                },
+            },
+            Equation::Ln(l) => format!("ln({})", l.to_typst()),
+            Equation::Equals(e) => format!("{} = {}", e.0.to_typst(), e.1.to_typst()),
+            Equation::Sin(s) => format!("sin({})", s.to_typst()),
+            Equation::Cos(c) => format!("cos({})", c.to_typst()),
+            Equation::Arcsin(s) => format!("arcsin({})", s.to_typst()),
+            Equation::Arccos(c) => format!("arccos({})", c.to_typst()),
+            Equation::Arctan(t) => format!("arctan({})", t.to_typst()),
+            Equation::Abs(a) => format!("abs({})", a.to_typst()),
+            Equation::Derivative((d, is_partial)) => {
+                let d_symbol = if *is_partial { "diff" } else { "d" };
+                format!(
+                    "frac({d_symbol}, {d_symbol} {}) {}",
+                    d.1.to_typst(),
+                    d.0.to_typst_bracketed(true)
+                )
+            }
+        }
+    }
+
+    fn to_typst_bracketed(&self, needs_brackets: bool) -> String {
+        if needs_brackets {
+            format!("({})", self.to_typst())
+        } else {
+            self.to_typst()
+        }
+    }
+
+    /// Formats the equation as Presentation MathML
+    pub fn to_mathml(&self) -> String {
+        format!(
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+            self.to_mathml_element()
+        )
+    }
+
+    fn to_mathml_element(&self) -> String {
+        match self {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) if *i < 0 => {
+                    format!("<mrow><mo>-</mo><mn>{}</mn></mrow>", i.unsigned_abs())
+                }
+                Variable::Integer(i) => format!("<mn>{i}</mn>"),
+                Variable::Rational(r) => format!(
+                    "<mfrac>{}{}</mfrac>",
+                    Equation::Variable(Variable::Integer(*r.numer())).to_mathml_element(),
+                    Equation::Variable(Variable::Integer(*r.denom())).to_mathml_element()
+                ),
+                Variable::Constant(c) => match c {
+                    Constant::PI => "<mi>π</mi>".to_string(),
+                    Constant::E => "<mi>e</mi>".to_string(),
+                },
+                Variable::Letter(l) => mathml_name(l),
+                Variable::Vector(v) => format!("<mover>{}<mo>→</mo></mover>", mathml_name(v)),
+            },
+            Equation::Negative(n) => format!("<mrow><mo>-</mo>{}</mrow>", n.to_mathml_bracketed()),
+            Equation::Addition(a) => format!(
+                "<mrow>{}</mrow>",
+                a.iter()
+                    .map(Equation::to_mathml_bracketed)
+                    .collect::<Vec<_>>()
+                    .join("<mo>+</mo>")
+            ),
+            Equation::Multiplication(m) => format!(
+                "<mrow>{}</mrow>",
+                m.iter()
+                    .map(Equation::to_mathml_bracketed)
+                    .collect::<Vec<_>>()
+                    .join("<mo>⋅</mo>")
+            ),
+            Equation::Division(d) => match &**d {
+                (Equation::Ln(argument), Equation::Ln(base)) => format!(
+                    "<mrow><msub><mi>log</mi>{}</msub>{}</mrow>",
+                    base.to_mathml_element(),
+                    mathml_application(argument)
+                ),
+                (numerator, denominator) => format!(
+                    "<mfrac>{}{}</mfrac>",
+                    numerator.to_mathml_element(),
+                    denominator.to_mathml_element()
+                ),
+            },
+            Equation::Power(p) => match &**p {
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() == 2 =>
+                {
+                    format!("<msqrt>{}</msqrt>", base.to_mathml_element())
+                }
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() > 2 =>
+                {
+                    format!(
+                        "<mroot>{}<mn>{}</mn></mroot>",
+                        base.to_mathml_element(),
+                        r.denom()
+                    )
+                }
+                (base, exponent) => format!(
+                    "<msup>{}{}</msup>",
+                    base.to_mathml_bracketed(),
+                    exponent.to_mathml_element()
+                ),
+            },
+            Equation::Ln(l) => format!("<mrow><mi>ln</mi>{}</mrow>", mathml_application(l)),
+            Equation::Equals(e) => format!(
+                "<mrow>{}<mo>=</mo>{}</mrow>",
+                e.0.to_mathml_element(),
+                e.1.to_mathml_element()
+            ),
+            Equation::Sin(s) => format!("<mrow><mi>sin</mi>{}</mrow>", mathml_application(s)),
+            Equation::Cos(c) => format!("<mrow><mi>cos</mi>{}</mrow>", mathml_application(c)),
+            Equation::Arcsin(s) => {
+                format!("<mrow><mi>arcsin</mi>{}</mrow>", mathml_application(s))
+            }
+            Equation::Arccos(c) => {
+                format!("<mrow><mi>arccos</mi>{}</mrow>", mathml_application(c))
+            }
+            Equation::Arctan(t) => {
+                format!("<mrow><mi>arctan</mi>{}</mrow>", mathml_application(t))
+            }
+            Equation::Abs(a) => {
+                format!("<mrow><mo>|</mo>{}<mo>|</mo></mrow>", a.to_mathml_element())
+            }
+            Equation::Derivative((d, is_partial)) => {
+                let differential = if *is_partial { "∂" } else { "d" };
+                format!(
+                    "<mrow><mfrac><mi>{differential}</mi><mrow><mi>{differential}</mi>{}</mrow></mfrac>{}</mrow>",
+                    d.1.to_mathml_element(),
+                    mathml_parenthesized(&d.0)
+                )
+            }
+        }
+    }
+
+    fn to_mathml_bracketed(&self) -> String {
+        if self.needs_to_be_bracketet() {
+            mathml_parenthesized(self)
+        } else {
+            self.to_mathml_element()
+        }
+    }
+}
+
+/// The operands in brackets, separated by `operator`
+fn infix(operands: &[String], operator: &str) -> String {
+    operands
+        .iter()
+        .map(|operand| format!("({operand})"))
+        .collect::<Vec<_>>()
+        .join(operator)
+}
+
+/// A variable name as a code identifier, dropping LaTeX backslashes and braces so `\epsilon_0`
+/// becomes `epsilon_0` and `M_{\odot}` becomes `M_odot`
+pub(super) fn identifier(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '\\' | '{' | '}'))
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// A variable name as a Wolfram symbol, which is its [`identifier`] with the part after the
+/// first underscore as a subscript, because `x_1` would be a pattern named `x` in Wolfram
+fn wolfram_name(name: &str) -> String {
+    match identifier(name).split_once('_') {
+        Some((base, subscript)) => {
+            format!("Subscript[{base}, {}]", subscript.replace('_', ""))
+        }
+        None => identifier(name),
+    }
+}
+
+/// The factors of a product in the order they are printed
+pub(super) fn latex_factors(factors: &[Equation], options: LatexOptions) -> Vec<&Equation> {
+    let mut ordered = factors.iter().collect::<Vec<_>>();
+    if options.coefficients_first {
+        ordered.sort_by_key(|factor| {
+            !matches!(
+                factor,
+                Equation::Variable(Variable::Integer(_) | Variable::Rational(_))
+            )
+        });
+    }
+    ordered
+}
+
+fn mathml_parenthesized(equation: &Equation) -> String {
+    format!(
+        "<mrow><mo>(</mo>{}<mo>)</mo></mrow>",
+        equation.to_mathml_element()
+    )
+}
+
+/// A bracketed function argument, preceded by the invisible function application operator
+fn mathml_application(argument: &Equation) -> String {
+    format!("<mo>&#x2061;</mo>{}", mathml_parenthesized(argument))
+}
+
+/// A variable name, with a LaTeX subscript like in `\epsilon_0` or `M_{\odot}` as `<msub>`
+fn mathml_name(name: &str) -> String {
+    match name.split_once('_') {
+        Some((base, subscript)) => {
+            let subscript = subscript
+                .strip_prefix('{')
+                .and_then(|subscript| subscript.strip_suffix('}'))
+                .unwrap_or(subscript);
+            let subscript = if subscript.chars().all(|c| c.is_ascii_digit()) {
+                format!("<mn>{subscript}</mn>")
+            } else {
+                mathml_identifier(subscript)
+            };
+            format!("<msub>{}{subscript}</msub>", mathml_identifier(base))
+        }
+        None => mathml_identifier(name),
+    }
+}
+
+fn mathml_identifier(name: &str) -> String {
+    match SYMBOLS.iter().find(|(command, _)| *command == name) {
+        Some((_, symbol)) => format!("<mi>{symbol}</mi>"),
+        None => format!(
+            "<mi>{}</mi>",
+            name.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        ),
+    }
+}
+
+/// A variable name in Typst math, with a subscript for names like `m_e` or `M_{\odot}`
+fn typst_name(name: &str) -> String {
+    match name.split_once('_') {
+        Some((base, subscript)) => {
+            let subscript = subscript
+                .strip_prefix('{')
+                .and_then(|subscript| subscript.strip_suffix('}'))
+                .unwrap_or(subscript);
+            let subscript = typst_identifier(subscript);
+            if subscript.chars().count() == 1 {
+                format!("{}_{subscript}", typst_identifier(base))
+            } else {
+                format!("{}_({subscript})", typst_identifier(base))
+            }
+        }
+        None => typst_identifier(name),
+    }
+}
+
+/// Typst reads several letters in a row as one symbol, so longer names are quoted as text
+fn typst_identifier(name: &str) -> String {
+    if let Some((_, symbol)) = SYMBOLS.iter().find(|(command, _)| *command == name) {
+        return symbol.to_string();
+    }
+    let name = name.trim_start_matches('\\');
+    if name.chars().count() == 1 || name.chars().all(|c| c.is_ascii_digit()) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
     }
 }
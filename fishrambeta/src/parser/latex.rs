@@ -0,0 +1,1027 @@
+use super::lexer::{tokenize, Token, TokenKind};
+use super::{ParseError, ParseErrorKind};
+use crate::math::{Constant, Equation, Variable};
+use num::{CheckedDiv, CheckedMul, Rational64};
+use std::ops::Range;
+
+/// Commands that take their arguments like TeX macros, as a group or a single token each
+const MACRO_COMMANDS: [(&str, usize); 3] = [("\\frac", 2), ("\\sqrt", 1), ("\\vec", 1)];
+
+/// Functions that may be raised to a power directly, as in `\sin^2 x`, and take their argument
+/// with or without brackets. `\log` takes an optional base as a subscript and defaults to base 10.
+pub(super) const FUNCTION_NAMES: [&str; 13] = [
+    "\\sin", "\\cos", "\\tan", "\\sec", "\\csc", "\\cot", "\\arcsin", "\\arccos", "\\arctan",
+    "\\ln", "\\log", "\\exp", "\\abs",
+];
+
+/// Binding power of a prefix `-` or `+`, which binds tighter than products but looser than powers
+const PREFIX_BINDING_POWER: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOperator {
+    Equals,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+impl BinaryOperator {
+    fn binding_power(self) -> u8 {
+        match self {
+            BinaryOperator::Equals => 1,
+            BinaryOperator::Add | BinaryOperator::Subtract => 2,
+            BinaryOperator::Multiply | BinaryOperator::Divide => 3,
+            BinaryOperator::Power => 5,
+        }
+    }
+
+    fn is_right_associative(self) -> bool {
+        self == BinaryOperator::Power
+    }
+}
+
+/// Precedence climbing parser over the tokens of a LaTeX string
+pub(super) struct LatexParser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+    implicit_multiplication: bool,
+    /// Whether a number as macro argument only takes its first digit, as in `\frac12`
+    single_digit_arguments: bool,
+}
+
+impl<'a> LatexParser<'a> {
+    pub(super) fn new(source: &'a str, implicit_multiplication: bool) -> Self {
+        LatexParser {
+            source,
+            tokens: tokenize(source),
+            position: 0,
+            implicit_multiplication,
+            single_digit_arguments: true,
+        }
+    }
+
+    /// Parses tokens from another input format, which are spelled like their LaTeX counterparts
+    pub(super) fn from_tokens(source: &'a str, tokens: Vec<Token>) -> Self {
+        LatexParser {
+            source,
+            tokens,
+            position: 0,
+            implicit_multiplication: true,
+            single_digit_arguments: false,
+        }
+    }
+
+    pub(super) fn parse(mut self) -> Result<Equation, ParseError> {
+        if self.tokens.is_empty() {
+            return Err(ParseError::new(
+                ParseErrorKind::EmptyInput,
+                0..self.source.len(),
+            ));
+        }
+        self.check_brackets()?;
+        let equation = self.parse_expression(0)?;
+        match self.peek() {
+            Some(token) => Err(self.unexpected_token(token)),
+            None => Ok(equation),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn text(&self, span: Range<usize>) -> &'a str {
+        &self.source[span]
+    }
+
+    /// An empty span at the current token, or at the end of the input
+    fn current_position(&self) -> Range<usize> {
+        let position = self
+            .peek()
+            .map_or(self.source.len(), |token| token.span.start);
+        position..position
+    }
+
+    fn unexpected_token(&self, token: &Token) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::UnexpectedToken(self.text(token.span.clone()).to_string()),
+            token.span.clone(),
+        )
+    }
+
+    fn check_brackets(&self) -> Result<(), ParseError> {
+        let mut open_brackets: Vec<&Token> = vec![];
+        for token in &self.tokens {
+            match token.kind {
+                TokenKind::Open(_) => open_brackets.push(token),
+                TokenKind::Close(close) => match open_brackets.pop() {
+                    None => {
+                        return Err(ParseError::new(
+                            ParseErrorKind::UnexpectedClosingBracket(close),
+                            token.span.clone(),
+                        ))
+                    }
+                    Some(Token {
+                        kind: TokenKind::Open(open),
+                        ..
+                    }) if matching_bracket(*open) != close => {
+                        return Err(ParseError::new(
+                            ParseErrorKind::MismatchedBracket {
+                                expected: matching_bracket(*open),
+                                found: close,
+                            },
+                            token.span.clone(),
+                        ))
+                    }
+                    Some(_) => {}
+                },
+                _ => {}
+            }
+        }
+        match open_brackets.pop() {
+            Some(Token {
+                kind: TokenKind::Open(open),
+                span,
+            }) => Err(ParseError::new(
+                ParseErrorKind::UnclosedBracket(*open),
+                span.clone(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the operator at the current position, and whether it is written out. Two operands
+    /// next to each other are multiplied without an operator token.
+    fn peek_operator(&self) -> Option<(BinaryOperator, bool)> {
+        let operator = match self.peek()?.kind {
+            TokenKind::Equals => BinaryOperator::Equals,
+            TokenKind::Plus => BinaryOperator::Add,
+            TokenKind::Minus => BinaryOperator::Subtract,
+            TokenKind::Times => BinaryOperator::Multiply,
+            TokenKind::Divide => BinaryOperator::Divide,
+            TokenKind::Caret => BinaryOperator::Power,
+            TokenKind::Number(_)
+            | TokenKind::Letter(_)
+            | TokenKind::Command(_)
+            | TokenKind::Operand(_)
+            | TokenKind::Open(_) => return Some((BinaryOperator::Multiply, false)),
+            TokenKind::Underscore | TokenKind::Close(_) => return None,
+        };
+        Some((operator, true))
+    }
+
+    fn parse_expression(&mut self, minimum_binding_power: u8) -> Result<Equation, ParseError> {
+        let mut lhs = self.parse_prefix()?;
+        // Whether lhs is an addition or multiplication built by this loop, which further terms of
+        // the same kind get appended to
+        let mut is_open_chain = false;
+
+        while let Some((operator, is_written)) = self.peek_operator() {
+            let binding_power = operator.binding_power();
+            if binding_power < minimum_binding_power {
+                break;
+            }
+            if is_written {
+                self.position += 1;
+            }
+            let rhs = if operator.is_right_associative() {
+                self.parse_expression(binding_power)?
+            } else {
+                self.parse_expression(binding_power + 1)?
+            };
+
+            lhs = match (operator, lhs) {
+                (BinaryOperator::Equals, lhs) => Equation::Equals(Box::new((lhs, rhs))),
+                (BinaryOperator::Add | BinaryOperator::Subtract, lhs) => {
+                    let rhs = if operator == BinaryOperator::Subtract {
+                        Equation::Negative(Box::new(rhs))
+                    } else {
+                        rhs
+                    };
+                    match lhs {
+                        Equation::Addition(mut terms) if is_open_chain => {
+                            terms.push(rhs);
+                            Equation::Addition(terms)
+                        }
+                        lhs => Equation::Addition(vec![lhs, rhs]),
+                    }
+                }
+                (BinaryOperator::Multiply, Equation::Multiplication(mut factors))
+                    if is_open_chain =>
+                {
+                    let last = factors.last_mut().expect("Chains are never empty");
+                    match scientific_notation(last, &rhs) {
+                        Some(scaled) => *last = scaled,
+                        None => factors.push(rhs),
+                    }
+                    Equation::Multiplication(factors)
+                }
+                (BinaryOperator::Multiply, lhs) => scientific_notation(&lhs, &rhs)
+                    .unwrap_or_else(|| Equation::Multiplication(vec![lhs, rhs])),
+                (BinaryOperator::Divide, lhs) => Equation::Division(Box::new((lhs, rhs))),
+                (BinaryOperator::Power, lhs) => Equation::Power(Box::new((lhs, rhs))),
+            };
+            is_open_chain = matches!(
+                operator,
+                BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply
+            );
+        }
+        Ok(lhs)
+    }
+
+    fn parse_prefix(&mut self) -> Result<Equation, ParseError> {
+        match self.peek().map(|token| &token.kind) {
+            Some(TokenKind::Minus) => {
+                self.position += 1;
+                let is_number = matches!(
+                    self.peek().map(|token| &token.kind),
+                    Some(TokenKind::Number(_))
+                );
+                match self.parse_expression(PREFIX_BINDING_POWER)? {
+                    Equation::Variable(Variable::Integer(n)) if is_number => {
+                        Ok(Equation::Variable(Variable::Integer(-n)))
+                    }
+                    operand => Ok(Equation::Negative(Box::new(operand))),
+                }
+            }
+            Some(TokenKind::Plus) => {
+                self.position += 1;
+                self.parse_expression(PREFIX_BINDING_POWER)
+            }
+            _ => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Equation, ParseError> {
+        let missing_operand =
+            ParseError::new(ParseErrorKind::MissingOperand, self.current_position());
+        let Some(token) = self.next_token() else {
+            return Err(missing_operand);
+        };
+        match token.kind {
+            TokenKind::Number(ref number) => parse_decimal(number).ok_or_else(|| {
+                ParseError::new(ParseErrorKind::InvalidNumber(number.clone()), token.span)
+            }),
+            TokenKind::Open(open) => Ok(self.parse_group(open)?.0),
+            TokenKind::Command(ref command) if macro_arity(command).is_some() => {
+                self.parse_macro(command, token.span.start)
+            }
+            TokenKind::Command(ref command) if FUNCTION_NAMES.contains(&command.as_str()) => {
+                self.parse_function(command)
+            }
+            TokenKind::Letter(_) | TokenKind::Command(_) => self.parse_name(&token),
+            TokenKind::Operand(equation) => Ok(equation),
+            _ => Err(missing_operand),
+        }
+    }
+
+    /// Parses the rest of a bracketed group up to its closing bracket, returning the group and
+    /// the span of the closing bracket. A group between bars is an absolute value.
+    fn parse_group(&mut self, open: char) -> Result<(Equation, Range<usize>), ParseError> {
+        let equation = self.parse_expression(0)?;
+        let close = self.expect_closing_bracket()?;
+        if open == '|' {
+            Ok((Equation::Abs(Box::new(equation)), close))
+        } else {
+            Ok((equation, close))
+        }
+    }
+
+    fn expect_closing_bracket(&mut self) -> Result<Range<usize>, ParseError> {
+        match self.next_token() {
+            Some(Token {
+                kind: TokenKind::Close(_),
+                span,
+            }) => Ok(span),
+            Some(token) => Err(self.unexpected_token(&token)),
+            None => unreachable!("Brackets are checked to be balanced before parsing"),
+        }
+    }
+
+    fn parse_macro(&mut self, command: &str, start: usize) -> Result<Equation, ParseError> {
+        if command == "\\frac" {
+            if let Some((variable, is_partial)) = self.parse_derivative_operator()? {
+                let operand = self.parse_function_argument()?;
+                return Ok(Equation::Derivative((
+                    Box::new((operand, variable)),
+                    is_partial,
+                )));
+            }
+        }
+        let expected = macro_arity(command).expect("Command must be a macro");
+        let index = if command == "\\sqrt"
+            && matches!(
+                self.peek().map(|token| &token.kind),
+                Some(TokenKind::Open('['))
+            ) {
+            self.position += 1;
+            let index = self.parse_expression(0)?;
+            self.expect_closing_bracket()?;
+            Some(index)
+        } else {
+            None
+        };
+        let mut arguments = vec![];
+        let mut end = self.tokens[self.position - 1].span.end;
+        while arguments.len() < expected {
+            let Some((argument, argument_end)) = self.parse_macro_argument()? else {
+                break;
+            };
+            arguments.push(argument);
+            end = argument_end;
+        }
+        if arguments.len() != expected {
+            return Err(ParseError::new(
+                ParseErrorKind::WrongArgumentCount {
+                    command: command.to_string(),
+                    expected,
+                    found: arguments.len(),
+                },
+                start..end,
+            ));
+        }
+
+        let mut arguments = arguments.into_iter();
+        let mut argument = || arguments.next().unwrap();
+        Ok(match command {
+            "\\frac" => Equation::Division(Box::new((argument(), argument()))),
+            "\\sqrt" => root(argument(), index),
+            "\\vec" => match argument() {
+                Equation::Variable(Variable::Letter(name)) => {
+                    Equation::Variable(Variable::Vector(name))
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnexpectedToken(self.text(start..end).to_string()),
+                        start..end,
+                    ))
+                }
+            },
+            _ => unreachable!("{command} is not a macro"),
+        })
+    }
+
+    /// Parses the arguments of a `\frac` that is a derivative operator, `\frac{d}{dx}` or
+    /// `\frac{\partial}{\partial x}`, returning the variable and whether the derivative is
+    /// partial. Other fractions are left in the input.
+    fn parse_derivative_operator(&mut self) -> Result<Option<(Equation, bool)>, ParseError> {
+        let kind = |offset: usize| {
+            self.tokens
+                .get(self.position + offset)
+                .map(|token| &token.kind)
+        };
+        let is_partial = match kind(1) {
+            Some(TokenKind::Letter('d')) => false,
+            Some(TokenKind::Command(command)) if command == "\\partial" => true,
+            _ => return Ok(None),
+        };
+        let is_operator = kind(0) == Some(&TokenKind::Open('{'))
+            && kind(2) == Some(&TokenKind::Close('}'))
+            && kind(3) == Some(&TokenKind::Open('{'))
+            && kind(4) == kind(1)
+            && matches!(kind(5), Some(TokenKind::Letter(_) | TokenKind::Command(_)));
+        if !is_operator {
+            return Ok(None);
+        }
+        let start = self.position;
+        let first = self.tokens[start + 5].clone();
+        self.position += 6;
+        let variable = self.parse_name(&first)?;
+        if let Some(TokenKind::Close('}')) = self.peek().map(|token| &token.kind) {
+            self.position += 1;
+            Ok(Some((variable, is_partial)))
+        } else {
+            self.position = start;
+            Ok(None)
+        }
+    }
+
+    /// Parses a single macro argument, which is either a bracketed group or a single token, and
+    /// returns it with the end of its span
+    fn parse_macro_argument(&mut self) -> Result<Option<(Equation, usize)>, ParseError> {
+        let Some(token) = self.peek().cloned() else {
+            return Ok(None);
+        };
+        let argument = match token.kind {
+            TokenKind::Open(open) => {
+                self.position += 1;
+                let (argument, close) = self.parse_group(open)?;
+                return Ok(Some((argument, close.end)));
+            }
+            TokenKind::Number(ref number) if self.single_digit_arguments && number.len() > 1 => {
+                // Only the first digit belongs to this argument, the rest stays in the input
+                let (digit, rest) = number.split_at(1);
+                let rest = tokenize(rest).into_iter().map(|rest_token| Token {
+                    kind: rest_token.kind,
+                    span: rest_token.span.start + token.span.start + 1
+                        ..rest_token.span.end + token.span.start + 1,
+                });
+                self.tokens.splice(self.position..=self.position, rest);
+                return Ok(Some((
+                    parse_decimal(digit).ok_or_else(|| {
+                        ParseError::new(
+                            ParseErrorKind::InvalidNumber(digit.to_string()),
+                            token.span.start..token.span.start + 1,
+                        )
+                    })?,
+                    token.span.start + 1,
+                )));
+            }
+            TokenKind::Number(_) => self.parse_atom()?,
+            TokenKind::Letter(c) => {
+                self.position += 1;
+                name_to_equation(c.to_string())
+            }
+            TokenKind::Operand(equation) => {
+                self.position += 1;
+                equation
+            }
+            TokenKind::Command(ref command) if !takes_arguments(command) => {
+                self.position += 1;
+                name_to_equation(command.clone())
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some((argument, token.span.end)))
+    }
+
+    /// Parses a function such as `\sin`, optionally raised to a power. A power of -1 on a
+    /// trigonometric function gives its inverse.
+    fn parse_function(&mut self, function: &str) -> Result<Equation, ParseError> {
+        let mut exponent = None;
+        let mut base = None;
+        loop {
+            match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::Caret) if exponent.is_none() => {
+                    self.position += 1;
+                    exponent = Some(self.parse_expression(BinaryOperator::Power.binding_power())?);
+                }
+                Some(TokenKind::Underscore) if function == "\\log" && base.is_none() => {
+                    self.position += 1;
+                    let missing_operand =
+                        ParseError::new(ParseErrorKind::MissingOperand, self.current_position());
+                    let (argument, _) = self.parse_macro_argument()?.ok_or(missing_operand)?;
+                    base = Some(argument);
+                }
+                _ => break,
+            }
+        }
+        let argument = self.parse_function_argument()?;
+
+        let is_inverse = exponent == Some(Equation::Variable(Variable::Integer(-1)));
+        let (function, exponent) = match function {
+            "\\sin" if is_inverse => ("\\arcsin", None),
+            "\\cos" if is_inverse => ("\\arccos", None),
+            "\\tan" if is_inverse => ("\\arctan", None),
+            // The reciprocal functions invert to the inverse of the reciprocal argument
+            "\\sec" | "\\csc" | "\\cot" if is_inverse => {
+                let reciprocal = Box::new(Equation::Division(Box::new((
+                    Equation::Variable(Variable::Integer(1)),
+                    argument,
+                ))));
+                return Ok(match function {
+                    "\\sec" => Equation::Arccos(reciprocal),
+                    "\\csc" => Equation::Arcsin(reciprocal),
+                    _ => Equation::Arctan(reciprocal),
+                });
+            }
+            function => (function, exponent),
+        };
+        let argument = Box::new(argument);
+        let applied = match function {
+            "\\sin" => Equation::Sin(argument),
+            "\\cos" => Equation::Cos(argument),
+            "\\tan" => Equation::Division(Box::new((
+                Equation::Sin(argument.clone()),
+                Equation::Cos(argument),
+            ))),
+            "\\sec" => Equation::Division(Box::new((
+                Equation::Variable(Variable::Integer(1)),
+                Equation::Cos(argument),
+            ))),
+            "\\csc" => Equation::Division(Box::new((
+                Equation::Variable(Variable::Integer(1)),
+                Equation::Sin(argument),
+            ))),
+            "\\cot" => Equation::Division(Box::new((
+                Equation::Cos(argument.clone()),
+                Equation::Sin(argument),
+            ))),
+            "\\arcsin" => Equation::Arcsin(argument),
+            "\\arccos" => Equation::Arccos(argument),
+            "\\arctan" => Equation::Arctan(argument),
+            "\\ln" => Equation::Ln(argument),
+            "\\log" => Equation::Division(Box::new((
+                Equation::Ln(argument),
+                Equation::Ln(Box::new(
+                    base.unwrap_or(Equation::Variable(Variable::Integer(10))),
+                )),
+            ))),
+            "\\abs" => Equation::Abs(argument),
+            "\\exp" => Equation::Power(Box::new((
+                Equation::Variable(Variable::Constant(Constant::E)),
+                *argument,
+            ))),
+            _ => unreachable!("{function} is not a function"),
+        };
+        Ok(match exponent {
+            Some(exponent) => Equation::Power(Box::new((applied, exponent))),
+            None => applied,
+        })
+    }
+
+    /// Parses the argument of a function. A bracketed argument ends at the closing bracket,
+    /// otherwise the argument runs over the implicitly multiplied factors that follow, up to an
+    /// operator or the next function, so `\sin 2x \cos x` is `\sin(2x)\cos(x)`.
+    fn parse_function_argument(&mut self) -> Result<Equation, ParseError> {
+        if let Some(&TokenKind::Open(open)) = self.peek().map(|token| &token.kind) {
+            self.position += 1;
+            return Ok(self.parse_group(open)?.0);
+        }
+
+        let mut factors = vec![self.parse_expression(PREFIX_BINDING_POWER)?];
+        while let Some(token) = self.peek() {
+            match &token.kind {
+                TokenKind::Number(_)
+                | TokenKind::Letter(_)
+                | TokenKind::Open(_)
+                | TokenKind::Operand(_) => {}
+                TokenKind::Command(command) if !FUNCTION_NAMES.contains(&command.as_str()) => {}
+                _ => break,
+            }
+            factors.push(self.parse_expression(PREFIX_BINDING_POWER)?);
+        }
+        Ok(if factors.len() == 1 {
+            factors.remove(0)
+        } else {
+            Equation::Multiplication(factors)
+        })
+    }
+
+    /// Parses a variable name with an optional subscript. Without implicit multiplication,
+    /// consecutive letters form a single name.
+    fn parse_name(&mut self, first: &Token) -> Result<Equation, ParseError> {
+        let mut name = self.symbol(first);
+        self.parse_subscript(&mut name)?;
+        if !self.implicit_multiplication {
+            while let Some(token) = self.peek() {
+                match &token.kind {
+                    TokenKind::Letter(_) | TokenKind::Number(_) => {}
+                    TokenKind::Command(command) if !takes_arguments(command) => {}
+                    _ => break,
+                }
+                name += &self.symbol(token);
+                self.position += 1;
+                self.parse_subscript(&mut name)?;
+            }
+        }
+
+        Ok(name_to_equation(name))
+    }
+
+    /// The name of a letter or command token. Names are taken from the token rather than the
+    /// input, since input formats other than LaTeX spell them differently.
+    fn symbol(&self, token: &Token) -> String {
+        match &token.kind {
+            TokenKind::Letter(c) => c.to_string(),
+            TokenKind::Command(command) => command.clone(),
+            _ => self.text(token.span.clone()).to_string(),
+        }
+    }
+
+    fn parse_subscript(&mut self, name: &mut String) -> Result<(), ParseError> {
+        if !matches!(
+            self.peek().map(|token| &token.kind),
+            Some(TokenKind::Underscore)
+        ) {
+            return Ok(());
+        }
+        self.position += 1;
+        let missing_operand =
+            ParseError::new(ParseErrorKind::MissingOperand, self.current_position());
+        let Some(token) = self.next_token() else {
+            return Err(missing_operand);
+        };
+        let end = match token.kind {
+            TokenKind::Letter(_) | TokenKind::Number(_) | TokenKind::Command(_) => token.span.end,
+            TokenKind::Open(_) => {
+                let mut depth = 1;
+                while depth > 0 {
+                    match self.next_token().map(|token| token.kind) {
+                        Some(TokenKind::Open(_)) => depth += 1,
+                        Some(TokenKind::Close(_)) => depth -= 1,
+                        Some(_) => {}
+                        None => unreachable!("Brackets are checked to be balanced before parsing"),
+                    }
+                }
+                self.tokens[self.position - 1].span.end
+            }
+            _ => return Err(missing_operand),
+        };
+        *name += "_";
+        *name += self.text(token.span.start..end);
+        Ok(())
+    }
+}
+
+fn macro_arity(command: &str) -> Option<usize> {
+    MACRO_COMMANDS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map(|(_, arity)| *arity)
+}
+
+pub(super) fn takes_arguments(command: &str) -> bool {
+    macro_arity(command).is_some() || FUNCTION_NAMES.contains(&command)
+}
+
+/// The `index`-th root of `radicand`, or the square root without an index
+pub(super) fn root(radicand: Equation, index: Option<Equation>) -> Equation {
+    let exponent = match index {
+        None => Equation::Variable(Variable::Rational(Rational64::new(1, 2))),
+        Some(Equation::Variable(Variable::Integer(n))) if n != 0 => {
+            Equation::Variable(Variable::Rational(Rational64::new(1, n)))
+        }
+        Some(index) => {
+            Equation::Division(Box::new((Equation::Variable(Variable::Integer(1)), index)))
+        }
+    };
+    Equation::Power(Box::new((radicand, exponent)))
+}
+
+fn name_to_equation(name: String) -> Equation {
+    match name.as_str() {
+        "\\pi" => Equation::Variable(Variable::Constant(Constant::PI)),
+        "e" => Equation::Variable(Variable::Constant(Constant::E)),
+        _ => Equation::Variable(Variable::Letter(name)),
+    }
+}
+
+/// Parses a number literal like `12`, `1.25`, `.5` or `6.02e23` into an exact value. When the
+/// scaled value doesn't fit, the power of ten is kept as a separate factor.
+fn parse_decimal(latex: &str) -> Option<Equation> {
+    let (mantissa, exponent) = match latex.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent.parse::<i64>().ok()?)),
+        None => (latex, None),
+    };
+    let mantissa = match mantissa.split_once('.') {
+        None => Variable::Integer(mantissa.parse().ok()?),
+        Some((integer_part, fractional_part)) => {
+            if fractional_part.is_empty() || fractional_part.contains('.') {
+                return None;
+            }
+            let denominator = 10_i64.checked_pow(fractional_part.len().try_into().ok()?)?;
+            let integer_part = if integer_part.is_empty() {
+                0
+            } else {
+                integer_part.parse::<i64>().ok()?
+            };
+            let numerator = integer_part
+                .checked_mul(denominator)?
+                .checked_add(fractional_part.parse().ok()?)?;
+            Variable::Rational(Rational64::new(numerator, denominator))
+        }
+    };
+    let mantissa = Equation::Variable(mantissa);
+    let Some(exponent) = exponent else {
+        return Some(mantissa);
+    };
+    let power = Equation::Power(Box::new((
+        Equation::Variable(Variable::Integer(10)),
+        Equation::Variable(Variable::Integer(exponent)),
+    )));
+    Some(
+        scientific_notation(&mantissa, &power)
+            .unwrap_or_else(|| Equation::Multiplication(vec![mantissa, power])),
+    )
+}
+
+/// Folds a number times an integer power of ten, as in `1.2\times10^{-3}`, into a single exact
+/// value. Returns `None` when the factors aren't of that form or the result would overflow.
+fn scientific_notation(mantissa: &Equation, power: &Equation) -> Option<Equation> {
+    let Equation::Power(power) = power else {
+        return None;
+    };
+    let (
+        Equation::Variable(Variable::Integer(10)),
+        Equation::Variable(Variable::Integer(exponent)),
+    ) = &**power
+    else {
+        return None;
+    };
+    match mantissa {
+        Equation::Variable(number) => {
+            scale_by_power_of_ten(number, *exponent).map(Equation::Variable)
+        }
+        Equation::Negative(number) => scientific_notation(number, &Equation::Power(power.clone()))
+            .map(|scaled| Equation::Negative(Box::new(scaled))),
+        _ => None,
+    }
+}
+
+fn scale_by_power_of_ten(number: &Variable, exponent: i64) -> Option<Variable> {
+    let number = match number {
+        Variable::Integer(n) => Rational64::from_integer(*n),
+        Variable::Rational(r) => *r,
+        _ => return None,
+    };
+    let factor =
+        Rational64::from_integer(10_i64.checked_pow(exponent.unsigned_abs().try_into().ok()?)?);
+    let scaled = if exponent < 0 {
+        number.checked_div(&factor)?
+    } else {
+        number.checked_mul(&factor)?
+    };
+    Some(if scaled.is_integer() {
+        Variable::Integer(scaled.to_integer())
+    } else {
+        Variable::Rational(scaled)
+    })
+}
+
+fn matching_bracket(c: char) -> char {
+    match c {
+        '(' => ')',
+        '{' => '}',
+        '[' => ']',
+        '|' => '|',
+        _ => unreachable!("{c} is not an opening bracket"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn x() -> Equation {
+        Equation::Variable(Variable::Letter("x".to_string()))
+    }
+    fn integer(n: i64) -> Equation {
+        Equation::Variable(Variable::Integer(n))
+    }
+
+    fn letter(name: &str) -> Equation {
+        Equation::Variable(Variable::Letter(name.to_string()))
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        assert_eq!(
+            Equation::from_latex("a*-b", false),
+            Equation::Multiplication(vec![letter("a"), Equation::Negative(Box::new(letter("b")))])
+        );
+        assert_eq!(
+            Equation::from_latex("2^-x", false),
+            Equation::Power(Box::new((integer(2), Equation::Negative(Box::new(x())))))
+        );
+        assert_eq!(
+            Equation::from_latex("-x^2", false),
+            Equation::Negative(Box::new(Equation::Power(Box::new((x(), integer(2))))))
+        );
+        assert_eq!(Equation::from_latex("-5", false), integer(-5));
+    }
+
+    #[test]
+    fn test_associativity() {
+        assert_eq!(
+            Equation::from_latex("a^b^c", false),
+            Equation::Power(Box::new((
+                letter("a"),
+                Equation::Power(Box::new((letter("b"), letter("c"))))
+            )))
+        );
+        assert_eq!(
+            Equation::from_latex("a-b-c+d", false),
+            Equation::Addition(vec![
+                letter("a"),
+                Equation::Negative(Box::new(letter("b"))),
+                Equation::Negative(Box::new(letter("c"))),
+                letter("d"),
+            ])
+        );
+        assert_eq!(
+            Equation::from_latex("a/b/c", false),
+            Equation::Division(Box::new((
+                Equation::Division(Box::new((letter("a"), letter("b")))),
+                letter("c")
+            )))
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(Equation::from_latex("xy", false), letter("xy"));
+        assert_eq!(
+            Equation::from_latex("m_e c^2", true),
+            Equation::Multiplication(vec![
+                letter("m_e"),
+                Equation::Power(Box::new((letter("c"), integer(2))))
+            ])
+        );
+        assert_eq!(
+            Equation::from_latex("M_{\\odot}x_12", true),
+            Equation::Multiplication(vec![letter("M_{\\odot}"), letter("x_1"), integer(2)])
+        );
+        assert_eq!(Equation::from_latex("e_0", false), letter("e_0"));
+        assert_eq!(
+            Equation::from_latex("\\left(x\\right)\\,\\pi", true),
+            Equation::Multiplication(vec![
+                x(),
+                Equation::Variable(Variable::Constant(Constant::PI))
+            ])
+        );
+    }
+
+    #[test]
+    fn test_long_expression() {
+        let latex = vec!["x"; 100_000].join("+");
+        let Equation::Addition(terms) = Equation::from_latex(&latex, false) else {
+            panic!("Expected an addition");
+        };
+        assert_eq!(terms.len(), 100_000);
+    }
+
+    #[test]
+    fn test_function_application() {
+        let sin_x = Equation::Sin(Box::new(x()));
+        assert_eq!(
+            Equation::from_latex("\\sin x \\cos x", false),
+            Equation::Multiplication(vec![sin_x.clone(), Equation::Cos(Box::new(x()))])
+        );
+        assert_eq!(
+            Equation::from_latex("\\sin^2 x", false),
+            Equation::Power(Box::new((sin_x.clone(), integer(2))))
+        );
+        assert_eq!(
+            Equation::from_latex("\\sin^{-1}(x)", false),
+            Equation::Arcsin(Box::new(x()))
+        );
+        let reciprocal_x = Box::new(Equation::Division(Box::new((integer(1), x()))));
+        assert_eq!(
+            Equation::from_latex("\\sec^{-1}(x)", false),
+            Equation::Arccos(reciprocal_x.clone())
+        );
+        assert_eq!(
+            Equation::from_latex("\\csc^{-1} x", false),
+            Equation::Arcsin(reciprocal_x.clone())
+        );
+        assert_eq!(
+            Equation::from_latex("\\cot^{-1}(x)", false),
+            Equation::Arctan(reciprocal_x)
+        );
+        assert_eq!(
+            Equation::from_latex("\\ln 2x", true),
+            Equation::Ln(Box::new(Equation::Multiplication(vec![integer(2), x()])))
+        );
+        assert_eq!(
+            Equation::from_latex("\\sin x+1", false),
+            Equation::Addition(vec![sin_x, integer(1)])
+        );
+        assert_eq!(
+            Equation::from_latex("\\frac12x", false),
+            Equation::Multiplication(vec![
+                Equation::Division(Box::new((integer(1), integer(2)))),
+                x()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_roots_and_logarithms() {
+        let ln = |argument| Equation::Ln(Box::new(argument));
+        assert_eq!(
+            Equation::from_latex("\\sqrt[3]{x}", false),
+            Equation::Power(Box::new((
+                x(),
+                Equation::Variable(Variable::Rational(Rational64::new(1, 3)))
+            )))
+        );
+        assert_eq!(
+            Equation::from_latex("\\log_2 x", false),
+            Equation::Division(Box::new((ln(x()), ln(integer(2)))))
+        );
+        assert_eq!(
+            Equation::from_latex("\\log(x)", false),
+            Equation::Division(Box::new((ln(x()), ln(integer(10)))))
+        );
+        assert_eq!(
+            Equation::from_latex("\\exp x", false),
+            Equation::Power(Box::new((
+                Equation::Variable(Variable::Constant(Constant::E)),
+                x()
+            )))
+        );
+        assert_eq!(
+            Equation::from_latex("\\cot x", false),
+            Equation::Division(Box::new((
+                Equation::Cos(Box::new(x())),
+                Equation::Sin(Box::new(x()))
+            )))
+        );
+
+        for latex in [
+            "\\sqrt{x}",
+            "\\sqrt[3]{x+1}",
+            "\\sqrt[n]{x}",
+            "\\log_{b} x",
+            "\\log_{10}(x)",
+            "\\exp(2x)",
+            "\\sec^2 x",
+            "\\csc x",
+        ] {
+            let equation = Equation::from_latex(latex, true);
+            assert_eq!(Equation::from_latex(&equation.to_latex(), true), equation);
+        }
+    }
+
+    #[test]
+    fn test_absolute_values() {
+        let abs = |argument| Equation::Abs(Box::new(argument));
+        assert_eq!(Equation::from_latex("|x|", false), abs(x()));
+        assert_eq!(
+            Equation::from_latex("|x+1|", false),
+            abs(Equation::Addition(vec![x(), integer(1)]))
+        );
+        assert_eq!(
+            Equation::from_latex("|x||y|", true),
+            Equation::Multiplication(vec![abs(x()), abs(letter("y"))])
+        );
+        assert_eq!(
+            Equation::from_latex("||x|-1|", false),
+            abs(Equation::Addition(vec![
+                abs(x()),
+                Equation::Negative(Box::new(integer(1)))
+            ]))
+        );
+        assert_eq!(
+            Equation::from_latex("\\left|x\\right|\\cdot \\lvert y\\rvert", false),
+            Equation::Multiplication(vec![abs(x()), abs(letter("y"))])
+        );
+        assert_eq!(
+            Equation::from_latex("\\sin|x|", false),
+            Equation::Sin(Box::new(abs(x())))
+        );
+        assert_eq!(
+            LatexParser::new("|x", false).parse(),
+            Err(ParseError::new(ParseErrorKind::UnclosedBracket('|'), 0..1))
+        );
+        assert_eq!(
+            LatexParser::new("x+|y", false).parse(),
+            Err(ParseError::new(ParseErrorKind::UnclosedBracket('|'), 2..3))
+        );
+
+        for latex in ["|x|", "|x+1|\\cdot |y|", "||x|-1|", "|-x|^{2}"] {
+            let equation = Equation::from_latex(latex, false);
+            assert_eq!(Equation::from_latex(&equation.to_latex(), false), equation);
+        }
+    }
+
+    #[test]
+    fn test_vectors_and_derivatives() {
+        let derivative = |equation, variable, is_partial| {
+            Equation::Derivative((Box::new((equation, variable)), is_partial))
+        };
+        assert_eq!(
+            Equation::from_latex("\\vec{v}", false),
+            Equation::Variable(Variable::Vector("v".to_string()))
+        );
+        assert_eq!(
+            Equation::from_latex("\\frac{d}{dx}x^2", false),
+            derivative(Equation::Power(Box::new((x(), integer(2)))), x(), false)
+        );
+        assert_eq!(
+            Equation::from_latex("\\frac{\\partial}{\\partial y}(xy)", true),
+            derivative(
+                Equation::Multiplication(vec![x(), letter("y")]),
+                letter("y"),
+                true
+            )
+        );
+        assert_eq!(
+            Equation::from_latex("\\frac{dy}{dx}", true),
+            Equation::Division(Box::new((
+                Equation::Multiplication(vec![letter("d"), letter("y")]),
+                Equation::Multiplication(vec![letter("d"), x()])
+            )))
+        );
+
+        for latex in [
+            "\\vec{v}\\cdot \\vec{w_1}",
+            "\\frac{d}{dx}(\\sin(x))+1",
+            "\\frac{d}{dt}(\\vec{r})^{2}",
+            "\\frac{\\partial}{\\partial x}(x\\cdot y)",
+        ] {
+            let equation = Equation::from_latex(latex, false);
+            assert_eq!(Equation::from_latex(&equation.to_latex(), false), equation);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use super::latex::takes_arguments;
+use crate::math::Equation;
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum TokenKind {
+    Number(String),
+    Letter(char),
+    Command(String),
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Caret,
+    Underscore,
+    Equals,
+    Open(char),
+    Close(char),
+    /// An operand that is already parsed, from structured input like MathML
+    Operand(Equation),
+}
+
+/// A single LaTeX token and the byte range it was read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+/// Commands that only affect spacing and can be skipped
+const SPACING_COMMANDS: [&str; 8] = [
+    "\\,", "\\;", "\\:", "\\!", "\\ ", "\\\\", "\\quad", "\\qquad",
+];
+
+/// Splits LaTeX into tokens. Whitespace and spacing commands are dropped, `\left` and `\right`
+/// are merged with the bracket they modify, `\cdot` and `\times` become a multiplication and
+/// absolute value bars become brackets.
+pub(super) fn tokenize(latex: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut bar_depth = 0;
+    let mut i = 0;
+    while let Some(c) = latex[i..].chars().next() {
+        let start = i;
+        i += c.len_utf8();
+        // Like in TeX, a subscript without braces only takes a single character
+        let is_subscript = tokens
+            .last()
+            .is_some_and(|token| token.kind == TokenKind::Underscore);
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                if !is_subscript {
+                    i += latex[i..]
+                        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                        .unwrap_or(latex.len() - i);
+                    i += exponent_length(&latex[i..]);
+                }
+                TokenKind::Number(latex[start..i].to_string())
+            }
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Times,
+            '/' => TokenKind::Divide,
+            '^' => TokenKind::Caret,
+            '_' => TokenKind::Underscore,
+            '=' => TokenKind::Equals,
+            '(' | '{' | '[' => TokenKind::Open(c),
+            ')' | '}' | ']' => TokenKind::Close(c),
+            '|' => bar(&tokens, &mut bar_depth),
+            '\\' => {
+                i += command_length(&latex[i..]);
+                match &latex[start..i] {
+                    "\\left" | "\\right" => {
+                        match read_delimiter(&latex[i..], &latex[start..i] == "\\left") {
+                            Some((Some(kind), length)) => {
+                                i += length;
+                                match kind {
+                                    TokenKind::Open('|') => bar_depth += 1,
+                                    TokenKind::Close('|') => bar_depth -= 1,
+                                    _ => {}
+                                }
+                                kind
+                            }
+                            Some((None, length)) => {
+                                i += length;
+                                continue;
+                            }
+                            None => TokenKind::Command(latex[start..i].to_string()),
+                        }
+                    }
+                    "\\{" => TokenKind::Open('{'),
+                    "\\}" => TokenKind::Close('}'),
+                    "\\lvert" => {
+                        bar_depth += 1;
+                        TokenKind::Open('|')
+                    }
+                    "\\rvert" => {
+                        bar_depth -= 1;
+                        TokenKind::Close('|')
+                    }
+                    "\\vert" => bar(&tokens, &mut bar_depth),
+                    "\\cdot" | "\\times" => TokenKind::Times,
+                    command if SPACING_COMMANDS.contains(&command) => continue,
+                    command => TokenKind::Command(command.to_string()),
+                }
+            }
+            c => TokenKind::Letter(c),
+        };
+        tokens.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+    tokens
+}
+
+/// Whether a `|` opens or closes an absolute value. It closes the innermost open one when it
+/// follows the end of an operand, so `|x||y|` is two absolute values and `||x|-1|` is nested.
+fn bar(tokens: &[Token], bar_depth: &mut i32) -> TokenKind {
+    let ends_operand = tokens.last().is_some_and(|token| match &token.kind {
+        TokenKind::Number(_) | TokenKind::Letter(_) | TokenKind::Close(_) => true,
+        TokenKind::Command(command) => !takes_arguments(command),
+        _ => false,
+    });
+    if *bar_depth > 0 && ends_operand {
+        *bar_depth -= 1;
+        TokenKind::Close('|')
+    } else {
+        *bar_depth += 1;
+        TokenKind::Open('|')
+    }
+}
+
+/// The length of the command name following a backslash, which is either a run of letters or a
+/// single other character
+fn command_length(latex: &str) -> usize {
+    match latex.find(|c: char| !c.is_ascii_alphabetic()) {
+        Some(0) => latex.chars().next().map_or(0, char::len_utf8),
+        Some(length) => length,
+        None => latex.len(),
+    }
+}
+
+/// The length of an `e` exponent directly following a number, as in `6.02e23` or `1e-3`
+pub(super) fn exponent_length(latex: &str) -> usize {
+    let Some(rest) = latex.strip_prefix(['e', 'E']) else {
+        return 0;
+    };
+    let sign_length = usize::from(rest.starts_with(['+', '-']));
+    let digits = rest[sign_length..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len() - sign_length);
+    if digits == 0 {
+        0
+    } else {
+        1 + sign_length + digits
+    }
+}
+
+/// Reads the delimiter following `\left` or `\right`, returning its bracket token (`None` for the
+/// invisible `.` delimiter) and the number of bytes read
+fn read_delimiter(latex: &str, is_left: bool) -> Option<(Option<TokenKind>, usize)> {
+    let trimmed = latex.trim_start();
+    let whitespace = latex.len() - trimmed.len();
+    let (kind, length) = if let Some(command) = trimmed.strip_prefix('\\') {
+        let length = 1 + command_length(command);
+        let kind = match &trimmed[..length] {
+            "\\{" => TokenKind::Open('{'),
+            "\\}" => TokenKind::Close('}'),
+            "\\vert" if is_left => TokenKind::Open('|'),
+            "\\vert" => TokenKind::Close('|'),
+            _ => return None,
+        };
+        (Some(kind), length)
+    } else {
+        let kind = match trimmed.chars().next()? {
+            c @ ('(' | '[') => Some(TokenKind::Open(c)),
+            c @ (')' | ']') => Some(TokenKind::Close(c)),
+            '|' if is_left => Some(TokenKind::Open('|')),
+            '|' => Some(TokenKind::Close('|')),
+            '.' => None,
+            _ => return None,
+        };
+        (kind, 1)
+    };
+    Some((kind, whitespace + length))
+}
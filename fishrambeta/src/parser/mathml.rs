@@ -0,0 +1,596 @@
+use super::latex::{root, LatexParser, FUNCTION_NAMES};
+use super::lexer::{Token, TokenKind};
+use super::{ParseError, ParseErrorKind};
+use crate::math::{Equation, Variable};
+use std::ops::Range;
+
+/// LaTeX commands and the character MathML writes them as
+pub(super) const SYMBOLS: [(&str, char); 39] = [
+    ("\\alpha", 'α'),
+    ("\\beta", 'β'),
+    ("\\gamma", 'γ'),
+    ("\\delta", 'δ'),
+    ("\\epsilon", 'ϵ'),
+    ("\\varepsilon", 'ε'),
+    ("\\zeta", 'ζ'),
+    ("\\eta", 'η'),
+    ("\\theta", 'θ'),
+    ("\\iota", 'ι'),
+    ("\\kappa", 'κ'),
+    ("\\lambda", 'λ'),
+    ("\\mu", 'μ'),
+    ("\\nu", 'ν'),
+    ("\\xi", 'ξ'),
+    ("\\pi", 'π'),
+    ("\\rho", 'ρ'),
+    ("\\sigma", 'σ'),
+    ("\\tau", 'τ'),
+    ("\\upsilon", 'υ'),
+    ("\\phi", 'ϕ'),
+    ("\\varphi", 'φ'),
+    ("\\chi", 'χ'),
+    ("\\psi", 'ψ'),
+    ("\\omega", 'ω'),
+    ("\\Gamma", 'Γ'),
+    ("\\Delta", 'Δ'),
+    ("\\Theta", 'Θ'),
+    ("\\Lambda", 'Λ'),
+    ("\\Xi", 'Ξ'),
+    ("\\Pi", 'Π'),
+    ("\\Sigma", 'Σ'),
+    ("\\Upsilon", 'Υ'),
+    ("\\Phi", 'Φ'),
+    ("\\Psi", 'Ψ'),
+    ("\\Omega", 'Ω'),
+    ("\\hbar", 'ℏ'),
+    ("\\odot", '⊙'),
+    ("\\infty", '∞'),
+];
+
+/// Entities that may appear in MathML besides the ones predefined by XML
+const ENTITIES: [(&str, char); 15] = [
+    ("lt", '<'),
+    ("gt", '>'),
+    ("amp", '&'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", ' '),
+    ("minus", '−'),
+    ("times", '×'),
+    ("sdot", '⋅'),
+    ("middot", '·'),
+    ("InvisibleTimes", '\u{2062}'),
+    ("it", '\u{2062}'),
+    ("ApplyFunction", '\u{2061}'),
+    ("af", '\u{2061}'),
+    ("pi", 'π'),
+];
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String, Range<usize>),
+}
+
+/// An XML element, with its namespace prefix and attributes dropped
+#[derive(Debug)]
+struct Element {
+    name: String,
+    children: Vec<Node>,
+    span: Range<usize>,
+}
+
+impl Element {
+    /// The child elements, ignoring whitespace between them
+    fn child_elements(&self) -> Result<Vec<&Element>, ParseError> {
+        let mut elements = vec![];
+        for child in &self.children {
+            match child {
+                Node::Element(element) => elements.push(element),
+                Node::Text(text, _) if text.trim().is_empty() => {}
+                Node::Text(text, span) => {
+                    return Err(invalid_mathml(
+                        format!("unexpected text '{}' in <{}>", text.trim(), self.name),
+                        span.clone(),
+                    ))
+                }
+            }
+        }
+        Ok(elements)
+    }
+
+    fn exact_children<const N: usize>(&self) -> Result<[&Element; N], ParseError> {
+        let children = self.child_elements()?;
+        let found = children.len();
+        children.try_into().map_err(|_| {
+            invalid_mathml(
+                format!("<{}> expects {N} children, found {found}", self.name),
+                self.span.clone(),
+            )
+        })
+    }
+
+    /// The text content of a token element like `<mi>`
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|child| match child {
+                Node::Text(text, _) => Some(text.as_str()),
+                Node::Element(_) => None,
+            })
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+}
+
+fn invalid_mathml(reason: String, span: Range<usize>) -> ParseError {
+    ParseError::new(ParseErrorKind::InvalidMathml(reason), span)
+}
+
+/// Parses Presentation MathML. Rows are tokenized like LaTeX, with layout elements like `<mfrac>`
+/// turned into operands, so operator precedence and function application work the same.
+pub(super) fn parse(source: &str) -> Result<Equation, ParseError> {
+    let root = XmlReader {
+        source,
+        position: 0,
+    }
+    .read_document()?;
+    convert(source, &root)
+}
+
+fn convert(source: &str, element: &Element) -> Result<Equation, ParseError> {
+    match element.name.as_str() {
+        "math" | "mrow" | "mstyle" | "mpadded" => {
+            parse_row(source, &element.child_elements()?, &element.span)
+        }
+        "semantics" => match element.child_elements()?.first() {
+            Some(first) => convert(source, first),
+            None => Err(invalid_mathml(
+                "<semantics> is empty".to_string(),
+                element.span.clone(),
+            )),
+        },
+        _ => parse_row(source, &[element], &element.span),
+    }
+}
+
+fn parse_row(
+    source: &str,
+    elements: &[&Element],
+    span: &Range<usize>,
+) -> Result<Equation, ParseError> {
+    if let [first, inner @ .., last] = elements {
+        if is_operator(first, "|") && is_operator(last, "|") {
+            return Ok(Equation::Abs(Box::new(parse_row(source, inner, span)?)));
+        }
+    }
+
+    let mut tokens = vec![];
+    let mut elements = elements.iter();
+    while let Some(element) = elements.next() {
+        if let Some((variable, is_partial)) = derivative_operator(source, element)? {
+            let Some(operand) = elements.next() else {
+                return Err(invalid_mathml(
+                    "derivative is not applied to anything".to_string(),
+                    element.span.clone(),
+                ));
+            };
+            tokens.push(Token {
+                kind: TokenKind::Operand(Equation::Derivative((
+                    Box::new((convert(source, operand)?, variable)),
+                    is_partial,
+                ))),
+                span: element.span.start..operand.span.end,
+            });
+            continue;
+        }
+        tokens.append(&mut tokenize(source, element)?);
+    }
+    if tokens.is_empty() {
+        return Err(invalid_mathml("row is empty".to_string(), span.clone()));
+    }
+    LatexParser::from_tokens(source, tokens).parse()
+}
+
+fn tokenize(source: &str, element: &Element) -> Result<Vec<Token>, ParseError> {
+    let token = |kind| Token {
+        kind,
+        span: element.span.clone(),
+    };
+    let operand = |equation| Ok(vec![token(TokenKind::Operand(equation))]);
+    match element.name.as_str() {
+        "mi" => Ok(vec![token(identifier(&element.text()))]),
+        "mn" => Ok(vec![token(TokenKind::Number(element.text()))]),
+        "mo" => Ok(operator(element)?.map(token).into_iter().collect()),
+        "mtext" if element.text().is_empty() => Ok(vec![]),
+        "mtext" => Ok(vec![token(TokenKind::Command(element.text()))]),
+        "mspace" => Ok(vec![]),
+        "math" | "mrow" | "mstyle" | "mpadded" | "semantics" => operand(convert(source, element)?),
+        "mfrac" => {
+            let [numerator, denominator] = element.exact_children()?;
+            operand(Equation::Division(Box::new((
+                convert(source, numerator)?,
+                convert(source, denominator)?,
+            ))))
+        }
+        "msqrt" => operand(root(
+            parse_row(source, &element.child_elements()?, &element.span)?,
+            None,
+        )),
+        "mroot" => {
+            let [radicand, index] = element.exact_children()?;
+            operand(root(
+                convert(source, radicand)?,
+                Some(convert(source, index)?),
+            ))
+        }
+        "msup" => {
+            let [base, exponent] = element.exact_children()?;
+            match function_name(base) {
+                Some(function) => Ok(vec![
+                    token(TokenKind::Command(function)),
+                    token(TokenKind::Caret),
+                    token(TokenKind::Operand(convert(source, exponent)?)),
+                ]),
+                None => Ok(vec![token(TokenKind::Operand(Equation::Power(Box::new(
+                    (convert(source, base)?, convert(source, exponent)?),
+                ))))]),
+            }
+        }
+        "msub" | "msubsup" => {
+            let children = element.child_elements()?;
+            let (base, subscript, exponent) = match (element.name.as_str(), &children[..]) {
+                ("msub", [base, subscript]) => (base, subscript, None),
+                ("msubsup", [base, subscript, exponent]) => (base, subscript, Some(exponent)),
+                _ => {
+                    return Err(invalid_mathml(
+                        format!(
+                            "<{}> has the wrong number of children, found {}",
+                            element.name,
+                            children.len()
+                        ),
+                        element.span.clone(),
+                    ))
+                }
+            };
+            let subscript = convert(source, subscript)?;
+            let mut tokens = match function_name(base) {
+                Some(function) => vec![
+                    token(TokenKind::Command(function)),
+                    token(TokenKind::Underscore),
+                    token(TokenKind::Operand(subscript)),
+                ],
+                None if base.name == "mi" => {
+                    let name = symbol_name(&identifier(&base.text()));
+                    let subscript = subscript.to_latex();
+                    let name = if subscript.chars().count() == 1 {
+                        format!("{name}_{subscript}")
+                    } else {
+                        format!("{name}_{{{subscript}}}")
+                    };
+                    vec![token(TokenKind::Command(name))]
+                }
+                None => {
+                    return Err(invalid_mathml(
+                        "only identifiers and functions can have a subscript".to_string(),
+                        base.span.clone(),
+                    ))
+                }
+            };
+            if let Some(exponent) = exponent {
+                tokens.push(token(TokenKind::Caret));
+                tokens.push(token(TokenKind::Operand(convert(source, exponent)?)));
+            }
+            Ok(tokens)
+        }
+        "mover" => {
+            let [base, accent] = element.exact_children()?;
+            let name = match base.name.as_str() {
+                "mi" => Some(symbol_name(&identifier(&base.text()))),
+                "msub" => match convert(source, base)? {
+                    Equation::Variable(Variable::Letter(name)) => Some(name),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match name {
+                Some(name) if is_operator(accent, "→") || is_operator(accent, "\u{20d7}") => {
+                    operand(Equation::Variable(Variable::Vector(name)))
+                }
+                _ => Err(invalid_mathml(
+                    "only vector arrows are supported over identifiers".to_string(),
+                    element.span.clone(),
+                )),
+            }
+        }
+        name => Err(invalid_mathml(
+            format!("unsupported element <{name}>"),
+            element.span.clone(),
+        )),
+    }
+}
+
+/// The token for the contents of an `<mi>`, which is a function, a symbol or a variable name
+fn identifier(text: &str) -> TokenKind {
+    let command = format!("\\{text}");
+    if FUNCTION_NAMES.contains(&command.as_str()) {
+        return TokenKind::Command(command);
+    }
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => match SYMBOLS.iter().find(|(_, symbol)| *symbol == c) {
+            Some((command, _)) => TokenKind::Command((*command).to_string()),
+            None => TokenKind::Letter(c),
+        },
+        _ => TokenKind::Command(text.to_string()),
+    }
+}
+
+fn symbol_name(token: &TokenKind) -> String {
+    match token {
+        TokenKind::Letter(c) => c.to_string(),
+        TokenKind::Command(command) => command.clone(),
+        _ => unreachable!("Identifiers are letters or commands"),
+    }
+}
+
+/// The token for an `<mo>`, or `None` for invisible operators like function application
+fn operator(element: &Element) -> Result<Option<TokenKind>, ParseError> {
+    let text = element.text();
+    let kind = match text.as_str() {
+        "" | "\u{2061}" | "\u{2063}" => return Ok(None),
+        "+" => TokenKind::Plus,
+        "-" | "−" => TokenKind::Minus,
+        "*" | "⋅" | "·" | "×" | "\u{2062}" => TokenKind::Times,
+        "/" | "÷" | "∕" => TokenKind::Divide,
+        "=" => TokenKind::Equals,
+        "(" | "[" | "{" => TokenKind::Open(text.chars().next().unwrap()),
+        ")" | "]" | "}" => TokenKind::Close(text.chars().next().unwrap()),
+        _ => match function_name(element) {
+            Some(function) => TokenKind::Command(function),
+            None => {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnexpectedToken(text),
+                    element.span.clone(),
+                ))
+            }
+        },
+    };
+    Ok(Some(kind))
+}
+
+fn is_operator(element: &Element, operator: &str) -> bool {
+    element.name == "mo" && element.text() == operator
+}
+
+/// The LaTeX command of a function written as `<mi>` or `<mo>`
+fn function_name(element: &Element) -> Option<String> {
+    if element.name != "mi" && element.name != "mo" {
+        return None;
+    }
+    let command = format!("\\{}", element.text());
+    FUNCTION_NAMES
+        .contains(&command.as_str())
+        .then_some(command)
+}
+
+/// Recognises the `d/dx` in front of a derivative, returning the variable and whether it is
+/// written as a partial derivative
+fn derivative_operator(
+    source: &str,
+    element: &Element,
+) -> Result<Option<(Equation, bool)>, ParseError> {
+    if element.name != "mfrac" {
+        return Ok(None);
+    }
+    let [numerator, denominator] = element.exact_children()?;
+    let d = numerator.text();
+    if !matches!(numerator.name.as_str(), "mi" | "mo") || !(d == "d" || d == "∂") {
+        return Ok(None);
+    }
+    match &denominator.child_elements()?[..] {
+        [differential, variable] if denominator.name == "mrow" && differential.text() == d => {
+            Ok(Some((convert(source, variable)?, d == "∂")))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Reads the subset of XML that MathML documents use
+struct XmlReader<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl XmlReader<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn read_document(mut self) -> Result<Element, ParseError> {
+        self.skip_misc()?;
+        if !self.rest().starts_with('<') {
+            let position = self.position;
+            return Err(if self.rest().is_empty() {
+                ParseError::new(ParseErrorKind::EmptyInput, 0..self.source.len())
+            } else {
+                invalid_mathml(
+                    "expected an element".to_string(),
+                    position..self.source.len(),
+                )
+            });
+        }
+        let root = self.read_element()?;
+        self.skip_misc()?;
+        if !self.rest().is_empty() {
+            return Err(invalid_mathml(
+                "unexpected content after the root element".to_string(),
+                self.position..self.source.len(),
+            ));
+        }
+        Ok(root)
+    }
+
+    /// Skips whitespace, comments, processing instructions and doctype declarations
+    fn skip_misc(&mut self) -> Result<(), ParseError> {
+        loop {
+            self.position = self.source.len() - self.rest().trim_start().len();
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<&str, ParseError> {
+        let start = self.position;
+        match self.rest().find(end) {
+            Some(length) => {
+                self.position += length + end.len();
+                Ok(&self.source[start..start + length])
+            }
+            None => Err(invalid_mathml(
+                format!("expected '{end}'"),
+                start..self.source.len(),
+            )),
+        }
+    }
+
+    fn read_element(&mut self) -> Result<Element, ParseError> {
+        let start = self.position;
+        self.position += 1;
+        let name = self.read_name();
+        if name.is_empty() {
+            return Err(invalid_mathml(
+                "expected an element name".to_string(),
+                start..self.position,
+            ));
+        }
+
+        // Attributes are skipped, quoted values may contain '>'
+        let mut quote = None;
+        loop {
+            let Some(c) = self.rest().chars().next() else {
+                return Err(invalid_mathml(
+                    format!("tag <{name}> is never closed"),
+                    start..self.source.len(),
+                ));
+            };
+            self.position += c.len_utf8();
+            match (c, quote) {
+                (c, Some(open)) if c == open => quote = None,
+                (_, Some(_)) => {}
+                ('"' | '\'', None) => quote = Some(c),
+                ('/', None) if self.rest().starts_with('>') => {
+                    self.position += 1;
+                    return Ok(Element {
+                        name,
+                        children: vec![],
+                        span: start..self.position,
+                    });
+                }
+                ('>', None) => break,
+                _ => {}
+            }
+        }
+
+        let mut children = vec![];
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(invalid_mathml(
+                    format!("element <{name}> is never closed"),
+                    start..self.source.len(),
+                ));
+            } else if rest.starts_with("</") {
+                let closing_start = self.position;
+                self.position += 2;
+                let closing_name = self.read_name();
+                self.skip_past(">")?;
+                if closing_name != name {
+                    return Err(invalid_mathml(
+                        format!("expected </{name}>, found </{closing_name}>"),
+                        closing_start..self.position,
+                    ));
+                }
+                return Ok(Element {
+                    name,
+                    children,
+                    span: start..self.position,
+                });
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.position += "<![CDATA[".len();
+                let text_start = self.position;
+                let text = self.skip_past("]]>")?.to_string();
+                children.push(Node::Text(text, text_start..self.position));
+            } else if rest.starts_with('<') {
+                children.push(Node::Element(self.read_element()?));
+            } else {
+                let text_start = self.position;
+                self.position += rest.find('<').unwrap_or(rest.len());
+                let span = text_start..self.position;
+                children.push(Node::Text(
+                    decode_entities(&self.source[span.clone()], text_start)?,
+                    span,
+                ));
+            }
+        }
+    }
+
+    /// Reads an element name, dropping its namespace prefix
+    fn read_name(&mut self) -> String {
+        let length = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .unwrap_or(self.rest().len());
+        let name = &self.source[self.position..self.position + length];
+        self.position += length;
+        name.rsplit(':').next().unwrap_or(name).to_string()
+    }
+}
+
+fn decode_entities(text: &str, offset: usize) -> Result<String, ParseError> {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded += &rest[..start];
+        let span_start = offset + text.len() - rest.len() + start;
+        let Some(length) = rest[start..].find(';') else {
+            return Err(invalid_mathml(
+                "entity is missing ';'".to_string(),
+                span_start..offset + text.len(),
+            ));
+        };
+        let entity = &rest[start + 1..start + length];
+        let c = if let Some(number) = entity.strip_prefix("#x") {
+            u32::from_str_radix(number, 16)
+                .ok()
+                .and_then(char::from_u32)
+        } else if let Some(number) = entity.strip_prefix('#') {
+            number.parse().ok().and_then(char::from_u32)
+        } else {
+            ENTITIES
+                .iter()
+                .find(|(name, _)| *name == entity)
+                .map(|(_, c)| *c)
+        };
+        let Some(c) = c else {
+            return Err(invalid_mathml(
+                format!("unknown entity '&{entity};'"),
+                span_start..span_start + length + 1,
+            ));
+        };
+        decoded.push(c);
+        rest = &rest[start + length + 1..];
+    }
+    decoded += rest;
+    Ok(decoded)
+}
@@ -1,396 +1,461 @@
-use num::Rational64;
-
-use crate::math::{Constant, Equation, Variable};
+use crate::math::Equation;
 use std::fmt;
 
+mod codegen;
+mod dot;
+mod error;
 pub mod formatters;
+mod latex;
+mod lexer;
+mod mathml;
+mod pretty;
+mod text;
+
+pub use error::{ParseError, ParseErrorKind};
 
 impl Equation {
+    /// Parses a LaTeX string into an equation
+    ///
+    /// # Panics
+    /// Panics when the LaTeX is malformed, use [`Equation::try_from_latex`] to handle that case
     pub fn from_latex(latex: &str, implicit_multiplication: bool) -> Equation {
-        //Cleanup steps
-        let mut cleaned_latex = latex
-            .replace("\\left(", "(")
-            .replace("\\right)", ")")
-            .replace("\\cdot", "*");
-        if !implicit_multiplication {
-            cleaned_latex = cleaned_latex.replace(' ', "");
+        match Equation::try_from_latex(latex, implicit_multiplication) {
+            Ok(equation) => equation,
+            Err(error) => panic!("Invalid LaTeX: {error}"),
         }
-
-        Equation::from_latex_internal(&cleaned_latex, implicit_multiplication)
     }
 
-    fn from_latex_internal(latex: &str, implicit_multiplication: bool) -> Equation {
-        if let Some((a, b)) = split_latex_at_operator(latex, &'=') {
-            return Equation::Equals(Box::new((
-                Equation::from_latex_internal(a, implicit_multiplication),
-                Equation::from_latex_internal(b, implicit_multiplication),
-            )));
-        }
-
-        if let Some((a, b)) = split_latex_at_operator(latex, &'+') {
-            return Equation::Addition(vec![
-                Equation::from_latex_internal(a, implicit_multiplication),
-                Equation::from_latex_internal(b, implicit_multiplication),
-            ]);
-        }
-
-        if let Some((a, b)) = split_latex_at_operator(latex, &'-') {
-            if latex.strip_prefix("-").is_none() {
-                return Equation::Addition(vec![
-                    Equation::from_latex_internal(a, implicit_multiplication),
-                    Equation::Negative(Box::new(Equation::from_latex_internal(
-                        b,
-                        implicit_multiplication,
-                    ))),
-                ]);
-            }
-        }
-
-        if let Some((a, b)) = split_latex_at_operator(latex, &'*') {
-            return Equation::Multiplication(vec![
-                Equation::from_latex_internal(a, implicit_multiplication),
-                Equation::from_latex_internal(b, implicit_multiplication),
-            ]);
-        }
-
-        if let Some((a, b)) = split_latex_at_operator(latex, &'/') {
-            return Equation::Division(Box::new((
-                Equation::from_latex_internal(a, implicit_multiplication),
-                Equation::from_latex_internal(b, implicit_multiplication),
-            )));
-        }
-
-        if let Ok(num) = latex.parse::<i64>() {
-            return Equation::Variable(Variable::Integer(num));
-        }
-
-        if let Some(stripped) = latex.strip_prefix("-") {
-            return Equation::Negative(Box::new(Equation::from_latex_internal(
-                stripped,
-                implicit_multiplication,
-            )));
-        }
-
-        if let Some((left, right)) = latex.split_once('.') {
-            if let (Ok(left_num), Ok(right_num)) = (left.parse::<i64>(), right.parse::<i64>()) {
-                assert!(right_num >= 0);
-                let log = right.len();
-                let denom = 10_i64.pow(log.try_into().unwrap());
-                let numer = left_num * denom + right_num;
-                return Equation::Variable(Variable::Rational((numer, denom).into()));
-            }
-        }
-
-        if let Some(parameters) = parse_latex_with_command(latex, "\\frac") {
-            assert_eq!(parameters.len(), 2);
-            return Equation::Division(Box::new((
-                Equation::from_latex_internal(parameters[0], implicit_multiplication),
-                Equation::from_latex_internal(parameters[1], implicit_multiplication),
-            )));
-        }
-
-        if is_in_redundant_brackets(latex) {
-            return Equation::from_latex_internal(
-                &latex[1..latex.len() - 1],
-                implicit_multiplication,
-            );
-        }
-
-        if let Some((a, b)) = split_latex_at_operator(latex, &'^') {
-            if implicit_multiplication {
-                let mut a_variables = split_into_variables(a);
-                let mut b_variables = split_into_variables(b);
-
-                let a_stripped = a_variables
-                    .pop()
-                    .expect("Left side of power cannot be empty");
-                let b_stripped = b_variables.remove(0);
-
-                let mut multiplication_parts: Vec<_> = a_variables
-                    .into_iter()
-                    .chain(b_variables)
-                    .map(|latex| Equation::from_latex_internal(latex, implicit_multiplication))
-                    .collect();
-
-                multiplication_parts.push(Equation::Power(Box::new((
-                    Equation::from_latex_internal(a_stripped, implicit_multiplication),
-                    Equation::from_latex_internal(b_stripped, implicit_multiplication),
-                ))));
-                return Equation::Multiplication(multiplication_parts);
-            }
-            return Equation::Power(Box::new((
-                Equation::from_latex_internal(a, implicit_multiplication),
-                Equation::from_latex_internal(b, implicit_multiplication),
-            )));
-        }
-
-        if let Some(parameters) = parse_latex_with_command(latex, "\\sqrt") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Power(Box::new((
-                Equation::from_latex_internal(parameters[0], implicit_multiplication),
-                Equation::Variable(Variable::Rational(Rational64::new(1, 2))),
-            )));
-        }
-
-        if let Some(parameters) = parse_latex_with_command(latex, "\\sin") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Sin(Box::new(Equation::from_latex_internal(
-                parameters[0],
-                implicit_multiplication,
-            )));
-        }
-
-        if let Some(parameters) = parse_latex_with_command(latex, "\\cos") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Cos(Box::new(Equation::from_latex_internal(
-                parameters[0],
-                implicit_multiplication,
-            )));
-        }
+    /// Parses a LaTeX string into an equation
+    ///
+    /// # Errors
+    /// Returns a [`ParseError`] with the span of the offending part of the original input when the
+    /// LaTeX is malformed
+    pub fn try_from_latex(
+        latex: &str,
+        implicit_multiplication: bool,
+    ) -> Result<Equation, ParseError> {
+        latex::LatexParser::new(latex, implicit_multiplication).parse()
+    }
 
-        if let Some(parameters) = parse_latex_with_command(latex, "\\tan") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Division(Box::new((
-                Equation::Sin(Box::new(Equation::from_latex_internal(
-                    parameters[0],
-                    implicit_multiplication,
-                ))),
-                Equation::Cos(Box::new(Equation::from_latex_internal(
-                    parameters[0],
-                    implicit_multiplication,
-                ))),
-            )));
+    /// Parses calculator-style text like `sin(x)^2 + 3*x/(1+y)` into an equation. Both `^` and
+    /// `**` are powers, `log` is the natural logarithm and words are single variables.
+    ///
+    /// # Panics
+    /// Panics when the text is malformed, use [`Equation::try_from_text`] to handle that case
+    pub fn from_text(text: &str) -> Equation {
+        match Equation::try_from_text(text) {
+            Ok(equation) => equation,
+            Err(error) => panic!("Invalid equation: {error}"),
         }
+    }
 
-        if let Some(parameters) = parse_latex_with_command(latex, "\\arcsin") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Arcsin(Box::new(Equation::from_latex_internal(
-                parameters[0],
-                implicit_multiplication,
-            )));
-        }
+    /// Parses calculator-style text into an equation
+    ///
+    /// # Errors
+    /// Returns a [`ParseError`] with the span of the offending part of the original input when the
+    /// text is malformed
+    pub fn try_from_text(text: &str) -> Result<Equation, ParseError> {
+        latex::LatexParser::from_tokens(text, text::tokenize(text)).parse()
+    }
 
-        if let Some(parameters) = parse_latex_with_command(latex, "\\arccos") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Arccos(Box::new(Equation::from_latex_internal(
-                parameters[0],
-                implicit_multiplication,
-            )));
+    /// Parses Presentation MathML into an equation
+    ///
+    /// # Panics
+    /// Panics when the MathML is malformed, use [`Equation::try_from_mathml`] to handle that case
+    pub fn from_mathml(mathml: &str) -> Equation {
+        match Equation::try_from_mathml(mathml) {
+            Ok(equation) => equation,
+            Err(error) => panic!("Invalid MathML: {error}"),
         }
+    }
 
-        if let Some(parameters) = parse_latex_with_command(latex, "\\arctan") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Arctan(Box::new(Equation::from_latex_internal(
-                parameters[0],
-                implicit_multiplication,
-            )));
-        }
+    /// Parses Presentation MathML into an equation
+    ///
+    /// # Errors
+    /// Returns a [`ParseError`] with the span of the offending element of the original input when
+    /// the MathML is malformed or uses unsupported elements
+    pub fn try_from_mathml(mathml: &str) -> Result<Equation, ParseError> {
+        mathml::parse(mathml)
+    }
+}
 
-        if let Some(parameters) = parse_latex_with_command(latex, "\\ln") {
-            assert_eq!(parameters.len(), 1);
-            return Equation::Ln(Box::new(Equation::from_latex_internal(
-                parameters[0],
-                implicit_multiplication,
-            )));
-        }
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_latex())
+    }
+}
 
-        let variable = if implicit_multiplication {
-            let mut variables = split_into_variables(latex);
-            if variables.len() > 1 {
-                return Equation::Multiplication(
-                    variables
-                        .into_iter()
-                        .map(|variable| {
-                            Equation::from_latex_internal(variable, implicit_multiplication)
-                        })
-                        .collect(),
-                );
-            }
-            variables.remove(0)
-        } else {
-            latex
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Variable;
+    use num::Rational64;
 
-        match variable {
-            "\\pi" => Equation::Variable(Variable::Constant(Constant::PI)),
-            "e" => Equation::Variable(Variable::Constant(Constant::E)),
-            letter => Equation::Variable(Variable::Letter(letter.to_string())),
-        }
+    fn parse_error(latex: &str) -> ParseError {
+        Equation::try_from_latex(latex, false).unwrap_err()
     }
-}
 
-fn is_in_redundant_brackets(latex: &str) -> bool {
-    if !is_opening_bracket(latex.chars().next().expect("Latex string cannot be empty"))
-        || !is_closing_bracket(latex.chars().last().expect("Latex string cannot be empty"))
-    {
-        return false;
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_error(""),
+            ParseError::new(ParseErrorKind::EmptyInput, 0..0)
+        );
+        assert_eq!(
+            parse_error("x+"),
+            ParseError::new(ParseErrorKind::MissingOperand, 2..2)
+        );
+        assert_eq!(
+            parse_error("\\frac{1}{x+2"),
+            ParseError::new(ParseErrorKind::UnclosedBracket('{'), 8..9)
+        );
+        assert_eq!(
+            parse_error("\\left(x+1\\right))"),
+            ParseError::new(ParseErrorKind::UnexpectedClosingBracket(')'), 16..17)
+        );
+        assert_eq!(
+            parse_error("\\sin(x}"),
+            ParseError::new(
+                ParseErrorKind::MismatchedBracket {
+                    expected: ')',
+                    found: '}'
+                },
+                6..7
+            )
+        );
+        assert_eq!(
+            parse_error("2\\cdot \\frac{1}"),
+            ParseError::new(
+                ParseErrorKind::WrongArgumentCount {
+                    command: "\\frac".to_string(),
+                    expected: 2,
+                    found: 1
+                },
+                7..15
+            )
+        );
+        assert_eq!(
+            parse_error("x+1.-5"),
+            ParseError::new(ParseErrorKind::InvalidNumber("1.".to_string()), 2..4)
+        );
+        assert_eq!(
+            parse_error("99999999999999999999"),
+            ParseError::new(
+                ParseErrorKind::InvalidNumber("99999999999999999999".to_string()),
+                0..20
+            )
+        );
     }
-    let mut current_depth = 0;
-    let length = latex.len();
-    for (i, c) in latex.chars().enumerate() {
-        if is_opening_bracket(c) {
-            current_depth += 1;
-        }
-        if is_closing_bracket(c) {
-            current_depth -= 1;
-        }
 
-        if current_depth == 0 {
-            return i + 1 == length;
-        }
+    #[test]
+    fn test_decimals() {
+        assert_eq!(
+            Equation::try_from_latex("-1.25", false),
+            Ok(Equation::Negative(Box::new(Equation::Variable(
+                Variable::Rational(Rational64::new(5, 4))
+            ))))
+        );
     }
-    panic!("Brackets are unbalanced in latex")
-}
 
-fn parse_latex_with_command<'a>(latex: &'a str, command: &'a str) -> Option<Vec<&'a str>> {
-    if !latex.starts_with(command) {
-        return None;
+    #[test]
+    fn test_scientific_notation() {
+        let rational = |n, d| Equation::Variable(Variable::Rational(Rational64::new(n, d)));
+        let integer = |n| Equation::Variable(Variable::Integer(n));
+        assert_eq!(Equation::try_from_latex(".5", false), Ok(rational(1, 2)));
+        assert_eq!(
+            Equation::try_from_latex("1.2\\times10^{-3}", false),
+            Ok(rational(3, 2500))
+        );
+        assert_eq!(
+            Equation::try_from_latex("3\\cdot 10^{5}", false),
+            Ok(integer(300_000))
+        );
+        assert_eq!(
+            Equation::try_from_latex("-2.5\\times 10^2", false),
+            Ok(Equation::Negative(Box::new(integer(250))))
+        );
+        assert_eq!(
+            Equation::try_from_latex("1.5e-3", false),
+            Ok(rational(3, 2000))
+        );
+        assert_eq!(
+            Equation::try_from_latex("6.02e23", false),
+            Ok(Equation::Multiplication(vec![
+                rational(301, 50),
+                Equation::Power(Box::new((integer(10), integer(23))))
+            ]))
+        );
+        assert_eq!(
+            Equation::try_from_latex("2e", false),
+            Ok(Equation::Multiplication(vec![
+                integer(2),
+                Equation::Variable(Variable::Constant(crate::math::Constant::E))
+            ]))
+        );
     }
-    let stripped_latex = &latex[command.len()..];
 
-    let mut current_depth = 0;
-    let mut parameter_indices: Vec<usize> = vec![];
-    for (i, c) in stripped_latex.chars().enumerate() {
-        if current_depth == 0 {
-            parameter_indices.push(i);
+    #[test]
+    fn test_from_text() {
+        for (text, latex) in [
+            ("sin(x)^2 + 3*x/(1+y)", "\\sin(x)^{2}+\\frac{3x}{1+y}"),
+            ("x**2 - 2x", "x^{2}-2x"),
+            ("sqrt(16) * exp(-x)", "\\sqrt{16}\\cdot e^{-x}"),
+            ("abs(theta) + pi", "\\abs(\\theta)+\\pi"),
+            ("epsilon_0 * E_1", "\\epsilon_0 \\cdot E_1"),
+            ("log(x)", "\\ln(x)"),
+            ("2.5e3", "2500"),
+        ] {
+            assert_eq!(
+                Equation::try_from_text(text),
+                Ok(Equation::from_latex(latex, true)),
+                "{text}"
+            );
         }
+        assert_eq!(
+            Equation::try_from_text("velocity*t"),
+            Ok(Equation::Multiplication(vec![
+                Equation::Variable(Variable::Letter("velocity".to_string())),
+                Equation::Variable(Variable::Letter("t".to_string())),
+            ]))
+        );
+        assert_eq!(
+            Equation::try_from_text("2 ** (x"),
+            Err(ParseError::new(ParseErrorKind::UnclosedBracket('('), 5..6))
+        );
+    }
 
-        if is_opening_bracket(c) {
-            current_depth += 1;
-        }
-        if is_closing_bracket(c) {
-            current_depth -= 1;
+    #[test]
+    fn test_mathml_round_trip() {
+        for latex in [
+            "x^2",
+            "\\frac{x}{x}",
+            "\\frac{\\frac{-x}{x}}{x}",
+            "\\frac{1}{\\left(x+4\\right)^2}",
+            "\\tan(x)",
+            "\\frac{x*\\frac{1}{\\cos(x)^2}-2*\\tan(x)}{x^3}",
+            "\\sin^{-1}(x)+\\arccos(y)-\\arctan(-3)",
+            "\\sqrt{x+1}\\cdot \\sqrt[3]{x}\\cdot x^{\\frac{2}{3}}",
+            "\\log_2 x+\\ln(e^{x})=\\pi",
+            "\\epsilon_0 \\cdot M_{\\odot}\\cdot v_{max}\\cdot \\hbar",
+            "-1.25+\\frac{-1}{2}",
+            "\\frac{d}{dx}(x^{2})+\\frac{d}{dt}(\\sin(t))",
+            "\\frac{\\partial}{\\partial y}(x\\cdot y)",
+            "\\vec{v}\\cdot \\vec{w_1}",
+            "|x+1|\\cdot |y|",
+        ] {
+            let equation = Equation::from_latex(latex, false);
+            let round_tripped = Equation::from_mathml(&equation.to_mathml());
+            assert_eq!(round_tripped.to_latex(), equation.to_latex(), "{latex}");
         }
     }
 
-    let mut parameters: Vec<&str> = vec![];
-    for i in 0..parameter_indices.len() - 1 {
-        parameters.push(&stripped_latex[parameter_indices[i] + 1..parameter_indices[i + 1] - 1]);
+    #[test]
+    fn test_from_mathml() {
+        let mathml = r#"<?xml version="1.0"?>
+            <math xmlns="http://www.w3.org/1998/Math/MathML" display="block">
+              <mrow>
+                <msup><mi>sin</mi><mn>2</mn></msup><mo>&ApplyFunction;</mo><mi>x</mi>
+                <mo>+</mo>
+                <mfrac><mi>d</mi><mrow><mi>d</mi><mi>x</mi></mrow></mfrac>
+                <mrow><mo>|</mo><mi>x</mi><mo>|</mo></mrow>
+              </mrow>
+            </math>"#;
+        let x = || Equation::Variable(Variable::Letter("x".to_string()));
+        assert_eq!(
+            Equation::try_from_mathml(mathml),
+            Ok(Equation::Addition(vec![
+                Equation::Power(Box::new((
+                    Equation::Sin(Box::new(x())),
+                    Equation::Variable(Variable::Integer(2))
+                ))),
+                Equation::Derivative((Box::new((Equation::Abs(Box::new(x())), x())), false))
+            ]))
+        );
+        assert_eq!(
+            Equation::try_from_mathml("<math><mfrac><mn>1</mn></mfrac></math>"),
+            Err(ParseError::new(
+                ParseErrorKind::InvalidMathml("<mfrac> expects 2 children, found 1".to_string()),
+                6..31
+            ))
+        );
+        assert_eq!(
+            Equation::try_from_mathml("<math><mi>x</mi>"),
+            Err(ParseError::new(
+                ParseErrorKind::InvalidMathml("element <math> is never closed".to_string()),
+                0..16
+            ))
+        );
     }
-    parameters.push(
-        &stripped_latex
-            [parameter_indices[parameter_indices.len() - 1] + 1..stripped_latex.len() - 1],
-    );
-    Some(parameters)
-}
 
-impl fmt::Display for Equation {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_latex())
+    #[test]
+    fn test_code_generators() {
+        let equation = Equation::from_latex("\\frac{1}{2}\\epsilon_0 \\cdot \\sin(x)^2", false);
+        assert_eq!(
+            equation.to_numpy(),
+            "((1)/(2))*(epsilon_0)*((np.sin(x))**(2))"
+        );
+        assert_eq!(
+            equation.to_sympy(),
+            "((sp.Integer(1))/(sp.Integer(2)))*(sp.Symbol(\"epsilon_0\"))*((sp.sin(sp.Symbol(\"x\")))**(sp.Integer(2)))"
+        );
+        assert_eq!(
+            equation.to_wolfram(),
+            "((1)/(2))*(Subscript[epsilon, 0])*((Sin[x])^(2))"
+        );
+        assert_eq!(equation.to_julia(), "((1)/(2))*(epsilon_0)*((sin(x))^(2))");
+
+        let equation = Equation::from_latex("M_{\\odot}=\\sqrt[3]{x}", false);
+        assert_eq!(equation.to_numpy(), "M_odot=np.power((x),((1)/(3)))");
+        assert_eq!(equation.to_wolfram(), "Subscript[M, odot] == (x)^((1)/(3))");
+        assert_eq!(equation.to_julia(), "M_odot == (x)^((1//3))");
+
+        let equation = Equation::from_latex("\\hbar \\cdot \\omega", false);
+        assert_eq!(equation.to_numpy(), "(hbar)*(omega)");
+        assert_eq!(equation.to_wolfram(), "(hbar)*(omega)");
+        assert_eq!(equation.to_julia(), "(hbar)*(omega)");
+
+        let equation = Equation::from_latex("\\frac{d}{dt}(\\vec{r})", false);
+        assert_eq!(equation.to_numpy(), "np.gradient(r, t)");
+        assert_eq!(equation.to_latex(), "\\frac{d}{dt}(\\vec{r})");
     }
+
+    #[test]
+    fn test_rust_and_c_functions() {
+        let equation = Equation::from_latex(
+            "E=\\sin(x)^2+\\frac{\\sin(x)}{m_e \\cdot \\hbar}-\\sqrt{2}",
+            false,
+        );
+        let parameters = [
+            Variable::Letter("x".to_string()),
+            Variable::Letter("m_e".to_string()),
+            Variable::Letter("\\hbar".to_string()),
+        ];
+        assert_eq!(
+            equation.to_rust_fn("energy", &parameters),
+            "pub fn energy(x: f64, m_e: f64, hbar: f64) -> f64 {
+    let t0 = f64::sin(x);
+    f64::powi(t0, 2) + t0 / (m_e * hbar) + -f64::sqrt(2.0)
 }
+"
+        );
+        assert_eq!(
+            equation.to_c_fn("energy", &parameters),
+            "double energy(double x, double m_e, double hbar) {
+    const double t0 = sin(x);
+    return pow(t0, 2.0) + t0 / (m_e * hbar) + -sqrt(2.0);
+}
+"
+        );
+    }
 
-fn split_latex_at_operator<'a>(latex: &'a str, operator: &'a char) -> Option<(&'a str, &'a str)> {
-    let mut current_depth = 0;
-    let mut right_start = latex.len();
-    for (i, c) in latex.chars().rev().enumerate() {
-        if is_opening_bracket(c) {
-            current_depth += 1;
-        }
-        if is_closing_bracket(c) {
-            current_depth -= 1;
+    #[test]
+    fn test_minimal_brackets() {
+        let letter = |name: &str| Equation::Variable(Variable::Letter(name.to_string()));
+        let integer = |n| Equation::Variable(Variable::Integer(n));
+        let negative = |equation| Equation::Negative(Box::new(equation));
+        let power = |base, exponent| Equation::Power(Box::new((base, exponent)));
+        for (equation, latex) in [
+            (
+                Equation::Addition(vec![letter("a"), negative(letter("b"))]),
+                "a-b",
+            ),
+            (
+                Equation::Addition(vec![
+                    letter("a"),
+                    Equation::Multiplication(vec![integer(-2), letter("x")]),
+                    integer(-3),
+                ]),
+                "a-2\\cdot x-3",
+            ),
+            (
+                Equation::Addition(vec![
+                    letter("a"),
+                    negative(Equation::Addition(vec![letter("b"), letter("c")])),
+                ]),
+                "a-(b+c)",
+            ),
+            (negative(negative(letter("x"))), "-(-x)"),
+            (
+                Equation::Multiplication(vec![negative(letter("x")), integer(2)]),
+                "-x\\cdot 2",
+            ),
+            (
+                Equation::Multiplication(vec![
+                    letter("x"),
+                    Equation::Addition(vec![letter("y"), integer(1)]),
+                ]),
+                "x\\cdot (y+1)",
+            ),
+            (power(letter("x"), integer(-1)), "\\frac{1}{x}"),
+            (power(letter("x"), integer(-2)), "\\frac{1}{x^{2}}"),
+            (power(integer(-2), integer(2)), "(-2)^{2}"),
+            (
+                power(
+                    Equation::Addition(vec![letter("x"), integer(1)]),
+                    integer(2),
+                ),
+                "(x+1)^{2}",
+            ),
+        ] {
+            assert_eq!(equation.to_latex(), latex);
+            assert_eq!(
+                Equation::from_latex(latex, false).to_latex(),
+                latex,
+                "{latex} does not round trip"
+            );
         }
 
-        if c == *operator && current_depth == 0 {
-            right_start = right_start - i - 1;
-            break;
-        }
-    }
-    if right_start == latex.len() {
-        None
-    } else {
-        Some((&latex[..right_start], &latex[right_start + 1..]))
+        let options = formatters::LatexOptions {
+            coefficients_first: true,
+        };
+        assert_eq!(
+            Equation::Multiplication(vec![letter("x"), integer(-2)]).to_latex_with_options(options),
+            "-2\\cdot x"
+        );
     }
-}
 
-fn split_into_variables(latex: &str) -> Vec<&str> {
-    let mut variables = Vec::new();
-    let mut split: Vec<&str> = Vec::new();
-    let mut remaining_latex = latex;
-    while let Some((a, b)) = split_latex_at_operator(remaining_latex, &' ') {
-        split.push(b);
-        remaining_latex = a;
-    }
-    if !remaining_latex.is_empty() {
-        split.push(remaining_latex);
-    }
-    for part in split.into_iter().rev() {
-        let mut i = 0;
-        while i < part.len() {
-            let next_i = i + get_index_of_next_variable_end(&part[i..]);
-            variables.push(&part[i..next_i]);
-            i = next_i;
-        }
+    #[test]
+    fn test_pretty() {
+        let pretty = |latex: &str| Equation::from_latex(latex, false).to_pretty();
+        assert_eq!(pretty("x^{2}-3\\cdot y"), "x² - 3⋅y");
+        assert_eq!(pretty("\\frac{x+1}{2}"), "x + 1\n─────\n  2");
+        assert_eq!(pretty("\\sqrt{x}+1"), " ┌─\n╲│x + 1");
+        assert_eq!(pretty("\\left(\\frac{a}{b}\\right)^{2}"), "⎛a⎞²\n⎜─⎟\n⎝b⎠");
+        assert_eq!(pretty("e^{x+y}"), " x + y\ne");
+        assert_eq!(pretty("\\epsilon_0"), "ϵ₀");
     }
-    variables
-}
 
-fn get_index_of_next_variable_end(latex: &str) -> usize {
-    let mut variable_type = VariableType::None;
-    let mut depth = 0;
-    for (i, c) in latex.chars().enumerate() {
-        if is_opening_bracket(c) {
-            depth += 1;
-        }
-        if is_closing_bracket(c) {
-            depth -= 1;
-        }
-        if depth != 0 {
-            continue;
-        }
-        match variable_type {
-            VariableType::None => {
-                if c == '\\' {
-                    variable_type = VariableType::Command;
-                    continue;
-                }
-                if c.is_ascii_digit() || c == '.' {
-                    variable_type = VariableType::Number;
-                    continue;
-                }
-                variable_type = VariableType::Letter;
-            }
-            VariableType::Command => {
-                if c == '_' {
-                    variable_type = VariableType::LetterWithSubscript;
-                    continue;
-                }
-                if c.is_ascii_digit() || c == '.' || c == '\\' {
-                    return i;
-                }
-            }
-            VariableType::Number => {
-                if !(c.is_ascii_digit() || c == '.') {
-                    return i;
-                }
-            }
-            VariableType::Letter => {
-                if c == '_' {
-                    variable_type = VariableType::LetterWithSubscript;
-                    continue;
-                }
-                return i;
-            }
-            VariableType::LetterWithSubscript => {
-                return i + 1;
-            }
-        }
+    #[test]
+    fn test_dot() {
+        assert_eq!(
+            Equation::from_latex("x+0.5", false).to_dot(),
+            "digraph equation {\n    ordering=out;\n    node [shape=box];\n    n0 [label=\"Addition\"];\n    n1 [label=\"Letter x\"];\n    n0 -> n1;\n    n2 [label=\"Rational 1/2\"];\n    n0 -> n2;\n}\n"
+        );
+
+        let mut step_logger = crate::math::steps::StepLogger::new();
+        step_logger.open_step(
+            Equation::from_latex("\\hbar+0", false),
+            Some("Remove \"0\""),
+        );
+        step_logger.close_step(Equation::from_latex("\\hbar", false));
+        let dot = step_logger.steps()[0].to_dot();
+        assert!(dot.contains("    label=\"Remove \\\"0\\\"\";\n"));
+        assert!(dot.contains("        before1 [label=\"Letter \\\\hbar\"];\n"));
+        assert!(dot.contains("        after0 [label=\"Letter \\\\hbar\"];\n"));
     }
-    latex.len()
-}
 
-fn is_opening_bracket(c: char) -> bool {
-    ['(', '{'].contains(&c)
-}
-fn is_closing_bracket(c: char) -> bool {
-    [')', '}'].contains(&c)
-}
-
-#[derive(Debug)]
-enum VariableType {
-    None,
-    Command,
-    Number,
-    Letter,
-    LetterWithSubscript,
+    #[test]
+    fn test_typst() {
+        let typst = |latex: &str| Equation::from_latex(latex, false).to_typst();
+        assert_eq!(
+            typst("\\frac{x+1}{2}-3\\cdot y^{2}"),
+            "frac(x + 1, 2) - 3 dot y^(2)"
+        );
+        assert_eq!(typst("\\sqrt{x}+\\sqrt[3]{y}"), "sqrt(x) + root(3, y)");
+        assert_eq!(typst("m_e\\cdot M_{\\odot}"), "m_e dot M_⊙");
+        assert_eq!(typst("\\sin(x_{10})=\\ln(\\alpha)"), "sin(x_(10)) = ln(α)");
+        assert_eq!(typst("\\log_{2}(x)"), "log_(2)(x)");
+        assert_eq!(typst("(a+b)^{2}"), "(a + b)^(2)");
+        assert_eq!(Equation::from_text("speed*t").to_typst(), "\"speed\" dot t");
+    }
 }
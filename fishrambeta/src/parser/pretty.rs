@@ -0,0 +1,407 @@
+use super::formatters::{latex_factors, LatexOptions, LatexPrecedence};
+use super::mathml::SYMBOLS;
+use crate::math::{Constant, Equation, Variable};
+
+const SUPERSCRIPTS: [(char, char); 21] = [
+    ('0', '⁰'),
+    ('1', '¹'),
+    ('2', '²'),
+    ('3', '³'),
+    ('4', '⁴'),
+    ('5', '⁵'),
+    ('6', '⁶'),
+    ('7', '⁷'),
+    ('8', '⁸'),
+    ('9', '⁹'),
+    ('+', '⁺'),
+    ('-', '⁻'),
+    ('=', '⁼'),
+    ('(', '⁽'),
+    (')', '⁾'),
+    ('n', 'ⁿ'),
+    ('i', 'ⁱ'),
+    ('x', 'ˣ'),
+    ('y', 'ʸ'),
+    ('k', 'ᵏ'),
+    ('m', 'ᵐ'),
+];
+
+const SUBSCRIPTS: [(char, char); 21] = [
+    ('0', '₀'),
+    ('1', '₁'),
+    ('2', '₂'),
+    ('3', '₃'),
+    ('4', '₄'),
+    ('5', '₅'),
+    ('6', '₆'),
+    ('7', '₇'),
+    ('8', '₈'),
+    ('9', '₉'),
+    ('+', '₊'),
+    ('-', '₋'),
+    ('=', '₌'),
+    ('a', 'ₐ'),
+    ('e', 'ₑ'),
+    ('i', 'ᵢ'),
+    ('j', 'ⱼ'),
+    ('k', 'ₖ'),
+    ('n', 'ₙ'),
+    ('o', 'ₒ'),
+    ('x', 'ₓ'),
+];
+
+/// A rectangle of text, with the row that lines up with the text around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Block {
+    lines: Vec<String>,
+    baseline: usize,
+}
+
+impl Block {
+    fn text(text: &str) -> Block {
+        Block {
+            lines: vec![text.to_string()],
+            baseline: 0,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.lines.iter().map(|line| width(line)).max().unwrap_or(0)
+    }
+
+    fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The row at `index`, padded to the width of the block
+    fn row(&self, index: usize) -> String {
+        let line = self.lines.get(index).map_or("", String::as_str);
+        format!("{line}{}", " ".repeat(self.width() - width(line)))
+    }
+
+    /// Places `other` to the right of this block with the baselines lined up
+    fn beside(self, other: &Block) -> Block {
+        let baseline = self.baseline.max(other.baseline);
+        let below = (self.height() - self.baseline).max(other.height() - other.baseline);
+        let row = |block: &Block, index: usize| match index.checked_sub(baseline - block.baseline) {
+            Some(index) if index < block.height() => block.row(index),
+            _ => " ".repeat(block.width()),
+        };
+        Block {
+            lines: (0..baseline + below)
+                .map(|index| row(&self, index) + &row(other, index))
+                .collect(),
+            baseline,
+        }
+    }
+
+    fn concat(blocks: impl IntoIterator<Item = Block>) -> Block {
+        blocks
+            .into_iter()
+            .reduce(|left, right| left.beside(&right))
+            .unwrap_or_else(|| Block::text(""))
+    }
+
+    /// The rows of the block centered in `width` columns
+    fn centered(&self, width: usize) -> Vec<String> {
+        let left = (width - self.width()) / 2;
+        (0..self.height())
+            .map(|index| {
+                let row = format!("{}{}", " ".repeat(left), self.row(index));
+                let right = width - self::width(&row);
+                row + &" ".repeat(right)
+            })
+            .collect()
+    }
+
+    fn fraction(numerator: &Block, denominator: &Block) -> Block {
+        let width = numerator.width().max(denominator.width());
+        let mut lines = numerator.centered(width);
+        let baseline = lines.len();
+        lines.push("─".repeat(width));
+        lines.extend(denominator.centered(width));
+        Block { lines, baseline }
+    }
+
+    /// Surrounds the block with brackets that are as tall as the block
+    fn bracketed(self) -> Block {
+        let (open, close) = match self.height() {
+            1 => (Block::text("("), Block::text(")")),
+            height => (
+                tall_bracket(height, self.baseline, ['⎛', '⎜', '⎝']),
+                tall_bracket(height, self.baseline, ['⎞', '⎟', '⎠']),
+            ),
+        };
+        Block::concat([open, self, close])
+    }
+
+    fn bracketed_if(self, needs_brackets: bool) -> Block {
+        if needs_brackets {
+            self.bracketed()
+        } else {
+            self
+        }
+    }
+
+    /// Puts `exponent` to the top right of the block, as superscript characters when possible
+    fn raised(self, exponent: Block) -> Block {
+        let superscript = match exponent.lines.as_slice() {
+            [line] => convert(line, &SUPERSCRIPTS),
+            _ => None,
+        };
+        let (exponent, rows_above) = if let Some(superscript) = superscript {
+            (Block::text(&superscript), 0)
+        } else {
+            let rows_above = exponent.height();
+            (exponent, rows_above)
+        };
+        let mut base = self;
+        let mut lines = vec![" ".repeat(base.width()); rows_above];
+        lines.append(&mut base.lines);
+        let base = Block {
+            lines,
+            baseline: base.baseline + rows_above,
+        };
+        let exponent = Block {
+            baseline: base.baseline,
+            lines: exponent.lines,
+        };
+        base.beside(&exponent)
+    }
+
+    /// Moves the block below the baseline like a subscript, as subscript characters when possible
+    fn lowered(self) -> Block {
+        if let [line] = self.lines.as_slice() {
+            if let Some(subscript) = convert(line, &SUBSCRIPTS) {
+                return Block::text(&subscript);
+            }
+        }
+        let mut lines = vec![String::new()];
+        lines.extend(self.lines);
+        Block { lines, baseline: 0 }
+    }
+
+    /// Draws a root sign with an overline over the block, with an optional index like `³`
+    fn root(self, index: Option<&str>) -> Block {
+        let index = index.unwrap_or("");
+        let indent = width(index).max(1);
+        let mut lines = vec![format!(
+            "{}{index}┌{}",
+            " ".repeat(indent - width(index)),
+            "─".repeat(self.width())
+        )];
+        for row in 0..self.height() {
+            let sign = if row + 1 == self.height() { '╲' } else { ' ' };
+            lines.push(format!(
+                "{}{sign}│{}",
+                " ".repeat(indent - 1),
+                self.row(row)
+            ));
+        }
+        Block {
+            lines,
+            baseline: self.baseline + 1,
+        }
+    }
+}
+
+fn tall_bracket(height: usize, baseline: usize, [top, middle, bottom]: [char; 3]) -> Block {
+    Block {
+        lines: (0..height)
+            .map(|row| match row {
+                0 => top,
+                row if row + 1 == height => bottom,
+                _ => middle,
+            })
+            .map(String::from)
+            .collect(),
+        baseline,
+    }
+}
+
+/// The number of columns a line takes up, combining characters like the vector arrow take none
+fn width(line: &str) -> usize {
+    line.chars()
+        .filter(|c| !matches!(c, '\u{0300}'..='\u{036f}' | '\u{20d0}'..='\u{20ff}'))
+        .count()
+}
+
+/// Converts every character of `text` with `table`, if all of them have a counterpart
+fn convert(text: &str, table: &[(char, char)]) -> Option<String> {
+    text.chars()
+        .map(|c| {
+            table
+                .iter()
+                .find(|(normal, _)| *normal == c)
+                .map(|(_, converted)| *converted)
+        })
+        .collect()
+}
+
+/// A variable name like `\epsilon_0` as it is printed, `ε₀`
+fn pretty_name(name: &str) -> String {
+    let symbol = |name: &str| match SYMBOLS.iter().find(|(command, _)| *command == name) {
+        Some((_, symbol)) => symbol.to_string(),
+        None => name.trim_start_matches('\\').to_string(),
+    };
+    match name.split_once('_') {
+        Some((base, subscript)) => {
+            let subscript = subscript
+                .strip_prefix('{')
+                .and_then(|subscript| subscript.strip_suffix('}'))
+                .unwrap_or(subscript);
+            let subscript = symbol(subscript);
+            match convert(&subscript, &SUBSCRIPTS) {
+                Some(converted) => symbol(base) + &converted,
+                None => format!("{}_{subscript}", symbol(base)),
+            }
+        }
+        None => symbol(name),
+    }
+}
+
+impl Equation {
+    /// Lays the equation out in two dimensions for a terminal, with fractions stacked over a
+    /// bar, exponents raised and roots drawn with box-drawing characters
+    pub fn to_pretty(&self) -> String {
+        self.to_pretty_block()
+            .lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn to_pretty_block(&self) -> Block {
+        let options = LatexOptions::default();
+        match self {
+            Equation::Variable(v) => match v {
+                Variable::Integer(i) => Block::text(&i.to_string()),
+                Variable::Rational(r) if *r.numer() < 0 => {
+                    Block::text("-").beside(&Block::fraction(
+                        &Block::text(&r.numer().unsigned_abs().to_string()),
+                        &Block::text(&r.denom().to_string()),
+                    ))
+                }
+                Variable::Rational(r) => Block::fraction(
+                    &Block::text(&r.numer().to_string()),
+                    &Block::text(&r.denom().to_string()),
+                ),
+                Variable::Constant(Constant::PI) => Block::text("π"),
+                Variable::Constant(Constant::E) => Block::text("e"),
+                Variable::Letter(l) => Block::text(&pretty_name(l)),
+                Variable::Vector(v) => Block::text(&format!("{}\u{20d7}", pretty_name(v))),
+            },
+            Equation::Negative(n) => Block::text("-").beside(
+                &n.to_pretty_block()
+                    .bracketed_if(n.latex_precedence(options) <= LatexPrecedence::Sum),
+            ),
+            Equation::Addition(a) => {
+                Block::concat(a.iter().enumerate().map(
+                    |(i, term)| match term.latex_negated(options) {
+                        Some(negated) if i > 0 => {
+                            Block::text(" - ").beside(&negated.to_pretty_block().bracketed_if(
+                                negated.latex_precedence(options) <= LatexPrecedence::Sum,
+                            ))
+                        }
+                        _ => {
+                            let is_nested =
+                                matches!(term, Equation::Addition(terms) if terms.len() > 1);
+                            let term = term
+                                .to_pretty_block()
+                                .bracketed_if(is_nested || matches!(term, Equation::Equals(_)));
+                            if i > 0 {
+                                Block::text(" + ").beside(&term)
+                            } else {
+                                term
+                            }
+                        }
+                    },
+                ))
+            }
+            Equation::Multiplication(m) => {
+                Block::concat(latex_factors(m, options).into_iter().enumerate().map(
+                    |(i, factor)| {
+                        let needs_brackets = match factor {
+                            Equation::Addition(terms) | Equation::Multiplication(terms) => {
+                                terms.len() > 1
+                            }
+                            Equation::Equals(_) => true,
+                            _ => i > 0 && factor.latex_negated(options).is_some(),
+                        };
+                        let factor = factor.to_pretty_block().bracketed_if(needs_brackets);
+                        if i > 0 {
+                            Block::text("⋅").beside(&factor)
+                        } else {
+                            factor
+                        }
+                    },
+                ))
+            }
+            Equation::Division(d) => match &**d {
+                (Equation::Ln(argument), Equation::Ln(base)) => Block::concat([
+                    Block::text("log"),
+                    base.to_pretty_block().lowered(),
+                    argument.to_pretty_block().bracketed(),
+                ]),
+                (numerator, denominator) => {
+                    Block::fraction(&numerator.to_pretty_block(), &denominator.to_pretty_block())
+                }
+            },
+            Equation::Power(p) => match &**p {
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() == 2 =>
+                {
+                    base.to_pretty_block().root(None)
+                }
+                (base, Equation::Variable(Variable::Rational(r)))
+                    if *r.numer() == 1 && *r.denom() > 2 =>
+                {
+                    let index = r.denom().to_string();
+                    let index = convert(&index, &SUPERSCRIPTS).unwrap_or(index);
+                    base.to_pretty_block().root(Some(&index))
+                }
+                (base, exponent) => match exponent.latex_negated(options) {
+                    Some(Equation::Variable(Variable::Integer(1))) => {
+                        Block::fraction(&Block::text("1"), &base.to_pretty_block())
+                    }
+                    Some(exponent) => Block::fraction(
+                        &Block::text("1"),
+                        &Equation::Power(Box::new((base.clone(), exponent))).to_pretty_block(),
+                    ),
+                    None => base
+                        .to_pretty_block()
+                        .bracketed_if(base.latex_precedence(options) < LatexPrecedence::Atom)
+                        .raised(exponent.to_pretty_block()),
+                },
+            },
+            Equation::Ln(l) => Block::text("ln").beside(&l.to_pretty_block().bracketed()),
+            Equation::Equals(e) => Block::concat([
+                e.0.to_pretty_block(),
+                Block::text(" = "),
+                e.1.to_pretty_block(),
+            ]),
+            Equation::Sin(s) => Block::text("sin").beside(&s.to_pretty_block().bracketed()),
+            Equation::Cos(c) => Block::text("cos").beside(&c.to_pretty_block().bracketed()),
+            Equation::Arcsin(s) => Block::text("arcsin").beside(&s.to_pretty_block().bracketed()),
+            Equation::Arccos(c) => Block::text("arccos").beside(&c.to_pretty_block().bracketed()),
+            Equation::Arctan(t) => Block::text("arctan").beside(&t.to_pretty_block().bracketed()),
+            Equation::Abs(a) => {
+                let argument = a.to_pretty_block();
+                let bar = tall_bracket(argument.height(), argument.baseline, ['│'; 3]);
+                Block::concat([bar.clone(), argument, bar])
+            }
+            Equation::Derivative((d, is_partial)) => {
+                let d_symbol = if *is_partial { "∂" } else { "d" };
+                Block::concat([
+                    Block::fraction(
+                        &Block::text(d_symbol),
+                        &Block::text(d_symbol).beside(&d.1.to_pretty_block()),
+                    ),
+                    d.0.to_pretty_block()
+                        .bracketed_if(d.0.latex_precedence(options) <= LatexPrecedence::Product),
+                ])
+            }
+        }
+    }
+}
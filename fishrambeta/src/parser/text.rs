@@ -0,0 +1,93 @@
+use super::lexer::{exponent_length, Token, TokenKind};
+
+/// Words with a special meaning in plain-text input, and the LaTeX command they stand for
+const WORDS: [(&str, &str); 19] = [
+    ("sin", "\\sin"),
+    ("cos", "\\cos"),
+    ("tan", "\\tan"),
+    ("sec", "\\sec"),
+    ("csc", "\\csc"),
+    ("cot", "\\cot"),
+    ("asin", "\\arcsin"),
+    ("arcsin", "\\arcsin"),
+    ("acos", "\\arccos"),
+    ("arccos", "\\arccos"),
+    ("atan", "\\arctan"),
+    ("arctan", "\\arctan"),
+    ("ln", "\\ln"),
+    ("log", "\\ln"),
+    ("exp", "\\exp"),
+    ("sqrt", "\\sqrt"),
+    ("abs", "\\abs"),
+    ("pi", "\\pi"),
+    ("hbar", "\\hbar"),
+];
+
+/// Greek letters, which are written as a word in plain text and as a command in LaTeX
+const GREEK_LETTERS: [&str; 34] = [
+    "alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta", "theta", "iota", "kappa",
+    "lambda", "mu", "nu", "xi", "rho", "sigma", "tau", "upsilon", "phi", "chi", "psi", "omega",
+    "Gamma", "Delta", "Theta", "Lambda", "Xi", "Pi", "Sigma", "Upsilon", "Phi", "Psi", "Omega",
+    "varphi",
+];
+
+/// Splits calculator-style text like `sin(x)^2 + 3*x/(1+y)` into the same tokens as the
+/// equivalent LaTeX. Words are single symbols, so function names, `pi` and Greek letters become
+/// their LaTeX commands and other words become variable names.
+pub(super) fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let start = i;
+        i += c.len_utf8();
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '0'..='9' | '.' => {
+                i += text[i..]
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(text.len() - i);
+                i += exponent_length(&text[i..]);
+                TokenKind::Number(text[start..i].to_string())
+            }
+            '*' if text[i..].starts_with('*') => {
+                i += 1;
+                TokenKind::Caret
+            }
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Times,
+            '/' => TokenKind::Divide,
+            '^' => TokenKind::Caret,
+            '_' => TokenKind::Underscore,
+            '=' => TokenKind::Equals,
+            '(' | '{' | '[' => TokenKind::Open(c),
+            ')' | '}' | ']' => TokenKind::Close(c),
+            c if c.is_alphabetic() => {
+                i += text[i..]
+                    .find(|c: char| !c.is_alphanumeric())
+                    .unwrap_or(text.len() - i);
+                word_to_token(&text[start..i])
+            }
+            c => TokenKind::Letter(c),
+        };
+        tokens.push(Token {
+            kind,
+            span: start..i,
+        });
+    }
+    tokens
+}
+
+fn word_to_token(word: &str) -> TokenKind {
+    if let Some((_, command)) = WORDS.iter().find(|(name, _)| *name == word) {
+        return TokenKind::Command((*command).to_string());
+    }
+    if GREEK_LETTERS.contains(&word) {
+        return TokenKind::Command(format!("\\{word}"));
+    }
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => TokenKind::Letter(c),
+        _ => TokenKind::Command(word.to_string()),
+    }
+}
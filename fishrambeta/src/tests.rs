@@ -1,7 +1,14 @@
-use crate::math::{Equation, Variable};
-use std::collections::BTreeMap;
+use crate::math::steps::StepLogger;
+use crate::math::{
+    cost, Assumption, Assumptions, Constraint, Convergence, Equation, Pattern, RuleErrorKind,
+    RuleSet, SaturationLimits, SimplifyOptions, Variable,
+};
+use num_rational::Rational64;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
 #[rustfmt::skip]
+#[allow(clippy::unreadable_literal)]
 const RANDOM_VALUES: [f64; 100] = [217.77919232197257, -35.022747163580675, -283.61757906755554, -422.8332777676821, -194.92360881854609, -477.86655577996, -29.012950819869673, -138.99250282163388, 217.37245037627065, 81.12293398936777, -484.6028233519494, -0.06141649619939926, -408.66081587017345, 454.5372144138511, 372.28974818102233, -339.08510157330574, 25.845056907138996, -6.623750578823376, 487.08176906403116, -235.16112120581255, -64.7076534048785, 379.04666015789155, -136.0099803454238, -270.1014077737798, -261.5338088646316, -299.83008733261875, 313.9502700105247, -436.3768060008657, 99.7130184799197, 253.665853120292, -485.56748448173124, -344.44107825401136, -305.60402556424737, -391.05762733119997, 259.79906875779363, -8.764033182361857, 401.89443171718904, 2.7298738221693952, -357.96757518076316, 30.066251011020086, 242.56134250311607, -188.3264692428852, -323.53321697284275, 46.36239256439262, 431.7482207979351, -32.9332217853173, -450.43980354035773, -313.44881729890176, -267.20467304181113, 43.682177656362, 250.4869482395178, -12.887344437762636, -9.100668379016554, 10.393089747143677, -31.534588565308354, 143.96249940655662, -110.11227778129404, -439.896803085285, 173.6373649619834, 176.99133234452813, -375.8187008434254, 365.2037962273764, -241.68433524572805, -464.88278829565945, -474.9472097056635, 214.6598812841528, 48.32911952122981, 199.99881004343263, -412.47194657311326, -386.77457496996414, -223.87912390602935, 115.87691202707208, -328.9029744692947, -376.33676502167845, -312.6983421683308, 302.39542101021084, -107.12349408768807, -444.4281036745549, -362.7697478836581, 180.4954544989173, -171.39029182793684, -73.87299139904479, 62.979932170930624, 289.7646491023522, 364.4356845596167, 160.76244782253548, -447.16766726529863, 161.07671711644014, 416.80878009610444, -73.91271610683026, 333.94896017534666, 228.33890219136163, 376.7379356120366, -390.77198880083984, 467.366395341391, 330.41419879022885, 161.37986540748682, -288.80853058314636, -152.20836571291431, -182.71524412760652];
 
 #[test]
@@ -19,6 +26,10 @@ fn differentiate() {
         "\\frac{\\tan(x)}{x^2}",
         "\\frac{x*\\frac{1}{\\cos(x)^2}-2*\\tan(x)}{x^3}",
     ));
+    assert!(derivative_is_equal(
+        "\\arctan(2\\cdot x)",
+        "\\frac{2}{1+4\\cdot x^2}"
+    ));
 }
 
 fn valuedicts() -> [BTreeMap<Variable, f64>; 30] {
@@ -54,8 +65,9 @@ fn derivative_is_equal(equation: &str, expected_result: &str) -> bool {
     approx_equal(derivative, correct)
 }
 
+#[allow(clippy::needless_pass_by_value)]
 pub fn approx_equal(a: Equation, b: Equation) -> bool {
-    println!("Comparing if approx equal: {} and {}", a, b);
+    println!("Comparing if approx equal: {a} and {b}");
     valuedicts()
         .iter()
         .all(|values| float_approx_equal(a.calculate(values), b.calculate(values)))
@@ -65,3 +77,588 @@ fn float_approx_equal(a: f64, b: f64) -> bool {
     let p = a / 10000.;
     (a - b).abs() < p.abs()
 }
+
+#[test]
+fn operators() {
+    let x = Equation::var("x");
+    assert_eq!(
+        crate::parse_latex!("x^{2}+3\\cdot x-\\frac{1}{x}"),
+        x.clone().pow(2) + Equation::from(3) * x.clone() - Equation::from(1) / x.clone()
+    );
+    assert_eq!(
+        crate::parse_latex!("\\sin(\\ln(x))\\cdot \\sqrt{x}"),
+        x.clone().ln().sin() * x.sqrt()
+    );
+}
+
+#[test]
+fn traversal() {
+    let equation = crate::parse_latex!("\\sin(x)+y\\cdot x^{2}");
+    assert_eq!(
+        equation.free_variables().into_iter().collect::<Vec<_>>(),
+        vec![
+            Variable::Letter("x".to_string()),
+            Variable::Letter("y".to_string())
+        ]
+    );
+    assert_eq!(
+        equation.iter_subexpressions().collect::<Vec<_>>()[..3],
+        [
+            &equation,
+            &crate::parse_latex!("\\sin(x)"),
+            &Equation::var("x")
+        ]
+    );
+    let leaves = equation.try_fold(0, |leaves, subexpression| {
+        Ok::<_, ()>(leaves + usize::from(subexpression.children().is_empty()))
+    });
+    assert_eq!(leaves, Ok(4));
+    let depth =
+        equation.fold(&mut |_, children: Vec<usize>| children.into_iter().max().unwrap_or(0) + 1);
+    assert_eq!(depth, 4);
+    assert_eq!(
+        equation.map_children(|child| -child),
+        -crate::parse_latex!("\\sin(x)") + -crate::parse_latex!("y\\cdot x^{2}")
+    );
+}
+
+#[test]
+fn substitute() {
+    let x = Variable::Letter("x".to_string());
+    let y = Variable::Letter("y".to_string());
+    let swap = HashMap::from([
+        (x.clone(), Equation::Variable(y.clone())),
+        (y.clone(), Equation::Variable(x.clone())),
+    ]);
+    assert_eq!(
+        crate::parse_latex!("x^{y}=2\\cdot y").substitute(&swap),
+        crate::parse_latex!("y^{x}=2\\cdot x")
+    );
+
+    let derivative = Equation::Derivative((
+        Box::new((crate::parse_latex!("x^{2}"), Equation::Variable(x.clone()))),
+        false,
+    ));
+    let at_three = HashMap::from([(x, Equation::from(3))]);
+    assert!(approx_equal(
+        derivative.substitute(&at_three),
+        Equation::from(6)
+    ));
+
+    assert_eq!(
+        crate::parse_latex!("\\sin(x)^{2}+\\cos(x)^{2}=\\sin(x)^{2}").replace_subexpression(
+            &crate::parse_latex!("\\sin(x)^{2}"),
+            &crate::parse_latex!("1-\\cos(x)^{2}")
+        ),
+        crate::parse_latex!("(1-\\cos(x)^{2})+\\cos(x)^{2}=1-\\cos(x)^{2}")
+    );
+}
+
+#[test]
+fn pattern_matching() {
+    let x = Variable::Letter("x".to_string());
+    let pattern =
+        Pattern::from_latex("?a\\cdot \\sin(?b)").constrain("a", Constraint::ConstantIn(x.clone()));
+    let bindings = crate::parse_latex!("y\\cdot \\sin(2\\cdot x)\\cdot 3")
+        .matches(&pattern)
+        .unwrap();
+    assert_eq!(bindings.get("a"), Some(&crate::parse_latex!("y\\cdot 3")));
+    assert_eq!(bindings.get("b"), Some(&crate::parse_latex!("2\\cdot x")));
+    assert_eq!(crate::parse_latex!("\\sin(x)").matches(&pattern), None);
+    assert_eq!(
+        crate::parse_latex!("\\sin(x)")
+            .matches(&pattern.clone().constrain("a", Constraint::Optional))
+            .unwrap()
+            .get("a"),
+        Some(&Equation::from(1))
+    );
+    assert_eq!(
+        crate::parse_latex!("x\\cdot \\sin(x)").matches(&pattern),
+        None
+    );
+
+    let distribute = Pattern::from_latex("?a\\cdot (?b+?c)");
+    assert!(crate::parse_latex!("x").matches(&distribute).is_none());
+    assert!(crate::parse_latex!("2").matches(&distribute).is_none());
+    assert!(crate::parse_latex!("x\\cdot y")
+        .matches(&distribute)
+        .is_none());
+    let bindings = crate::parse_latex!("x\\cdot (y+z)")
+        .matches(&distribute)
+        .unwrap();
+    assert_eq!(bindings.get("a"), Some(&Equation::var("x")));
+
+    let square = Pattern::from_latex("?a^{?n}+?a").constrain("n", Constraint::Integer);
+    assert!(crate::parse_latex!("y+y^{2}").matches(&square).is_some());
+    assert!(crate::parse_latex!("x+y^{2}").matches(&square).is_none());
+    assert!(crate::parse_latex!("y+y^{z}").matches(&square).is_none());
+
+    assert!(integral_is_equal(
+        "\\sin(2\\cdot x)",
+        "-\\frac{\\cos(2\\cdot x)}{2}"
+    ));
+    assert!(integral_is_equal(
+        "\\cos(3\\cdot x+1)",
+        "\\frac{\\sin(3\\cdot x+1)}{3}"
+    ));
+    assert!(integral_is_equal(
+        "x\\cdot (x+1)",
+        "\\frac{x^{3}}{3}+\\frac{x^{2}}{2}"
+    ));
+    assert!(integral_is_equal("(x+1)\\cdot (x-1)", "\\frac{x^{3}}{3}-x"));
+    assert!(integral_is_equal("(x+1)^{90}", "\\frac{(x+1)^{91}}{91}"));
+    assert!(integral_is_equal(
+        "(2\\cdot x+1)^{20}",
+        "\\frac{(2\\cdot x+1)^{21}}{42}"
+    ));
+    assert!(integral_is_equal(
+        "\\frac{1}{3\\cdot x+1}",
+        "\\frac{\\ln(|3\\cdot x+1|)}{3}"
+    ));
+    assert!(integral_is_equal(
+        "\\frac{x^{2}-1}{x-1}",
+        "\\frac{x^{2}}{2}+x"
+    ));
+}
+
+fn integral_is_equal(equation: &str, expected_result: &str) -> bool {
+    let x = Variable::Letter("x".to_string());
+    let integral = Equation::from_latex(equation, false)
+        .integrate(&x, &mut None)
+        .simplify_until_complete(&mut None);
+    approx_equal(integral, Equation::from_latex(expected_result, false))
+}
+
+#[test]
+fn rewrite_rules() {
+    let rules = RuleSet::parse(
+        "# Identities
+        pythagoras: \\sin(?a)^{2}+\\cos(?a)^{2} -> 1
+        thin lens: \\frac{1}{?u}+\\frac{1}{?v} -> \\frac{1}{f}
+        power of a power: (?a^{?m})^{?n} -> ?a^{?m\\cdot ?n} if ?n integer",
+    )
+    .unwrap();
+    assert_eq!(rules.rules().len(), 3);
+
+    let mut step_logger = Some(StepLogger::new());
+    let rewritten = crate::parse_latex!("x+\\cos(2\\cdot y)^{2}+\\sin(2\\cdot y)^{2}")
+        .rewrite(&rules, &mut step_logger);
+    assert_eq!(rewritten, crate::parse_latex!("1+x"));
+    let step_logger = step_logger.unwrap();
+    assert_eq!(step_logger.steps()[0].message(), Some("pythagoras"));
+
+    assert_eq!(
+        crate::parse_latex!("\\frac{1}{a}+\\frac{1}{b}").rewrite(&rules, &mut None),
+        crate::parse_latex!("\\frac{1}{f}")
+    );
+    assert_eq!(
+        crate::parse_latex!("(x^{2})^{3}").rewrite(&rules, &mut None),
+        crate::parse_latex!("x^{2\\cdot 3}")
+    );
+    assert_eq!(
+        crate::parse_latex!("(x^{2})^{y}").rewrite(&rules, &mut None),
+        crate::parse_latex!("(x^{2})^{y}")
+    );
+
+    let distribute = RuleSet::parse(
+        "distribute: ?a\\cdot (?b+?c) -> ?a\\cdot ?b+?a\\cdot ?c
+        commute: ?a+?b -> ?b+?a",
+    )
+    .unwrap();
+    // Commuting the sum back and forth stops at the first repeated form
+    assert_eq!(
+        crate::parse_latex!("x\\cdot (y+z)").rewrite(&distribute, &mut None),
+        crate::parse_latex!("x\\cdot y+x\\cdot z")
+    );
+    assert_eq!(
+        crate::parse_latex!("x").rewrite(&distribute, &mut None),
+        crate::parse_latex!("x")
+    );
+
+    assert_eq!(RuleSet::parse("\n\nbroken: x+ -> 1").unwrap_err().line, 3);
+    assert_eq!(
+        RuleSet::parse("no arrow: x = 1").unwrap_err().kind,
+        RuleErrorKind::MissingArrow
+    );
+    assert_eq!(
+        RuleSet::parse("bad: x -> 1 if ?x prime").unwrap_err().kind,
+        RuleErrorKind::InvalidCondition("?x prime".to_string())
+    );
+}
+
+#[test]
+fn saturation() {
+    let limits = SaturationLimits::default();
+    let factored =
+        crate::parse_latex!("x\\cdot y+x\\cdot z").simplify_saturate(cost::node_count, limits);
+    assert_eq!(cost::node_count(&factored), 5);
+    assert!(approx_equal(factored, crate::parse_latex!("x\\cdot (y+z)")));
+    assert_eq!(
+        crate::parse_latex!("a+\\sin(x)^{2}+\\cos(x)^{2}")
+            .simplify_saturate(cost::node_count, limits),
+        crate::parse_latex!("a+1")
+    );
+    assert_eq!(
+        crate::parse_latex!("x^{-2}").simplify_saturate(cost::node_count, limits),
+        crate::parse_latex!("x^{-2}")
+    );
+    assert_eq!(
+        crate::parse_latex!("x^{-2}").simplify_saturate(cost::no_negative_exponents, limits),
+        crate::parse_latex!("\\frac{1}{x^{2}}")
+    );
+    assert_eq!(
+        crate::parse_latex!("(x+1)^{2}-x^{2}").simplify_saturate(cost::node_count, limits),
+        crate::parse_latex!("1+2\\cdot x")
+    );
+    assert_eq!(cost::depth(&crate::parse_latex!("x\\cdot (y+z)")), 3);
+
+    let limited = SaturationLimits {
+        max_iterations: 0,
+        ..limits
+    };
+    assert_eq!(
+        crate::parse_latex!("x\\cdot y+x\\cdot z").simplify_saturate(cost::node_count, limited),
+        crate::parse_latex!("x\\cdot y+x\\cdot z")
+    );
+}
+
+#[test]
+fn assumptions() {
+    let x = Variable::Letter("x".to_string());
+    let positive = Assumptions::parse("x > 0").unwrap();
+    let simplify = |equation: Equation, assumptions: &Assumptions| {
+        equation.simplify_until_complete_with_assumptions(assumptions, &mut None)
+    };
+
+    assert_eq!(
+        simplify(crate::parse_latex!("\\sqrt{x^{2}}"), &Assumptions::new()),
+        Equation::var("x").abs()
+    );
+    assert_eq!(
+        simplify(crate::parse_latex!("\\sqrt{x^{2}}"), &positive),
+        crate::parse_latex!("x")
+    );
+    assert_eq!(
+        simplify((-Equation::var("x")).abs(), &positive),
+        crate::parse_latex!("x")
+    );
+    assert_eq!(
+        simplify(crate::parse_latex!("|-2\\cdot x|"), &positive),
+        crate::parse_latex!("2\\cdot x")
+    );
+    assert_eq!(
+        simplify(crate::parse_latex!("|-2\\cdot x|"), &Assumptions::new()),
+        crate::parse_latex!("|2\\cdot x|")
+    );
+    assert_eq!(
+        simplify(Equation::var("x").pow(2).abs(), &Assumptions::new()),
+        crate::parse_latex!("x^{2}")
+    );
+    assert_eq!(
+        simplify(crate::parse_latex!("\\ln(e^{x})"), &Assumptions::new()),
+        crate::parse_latex!("x")
+    );
+
+    assert_eq!(
+        simplify(crate::parse_latex!("\\frac{x}{x}"), &Assumptions::new()),
+        crate::parse_latex!("\\frac{x}{x}")
+    );
+    assert_eq!(
+        simplify(crate::parse_latex!("\\frac{x}{x}"), &Assumptions::lenient()),
+        crate::parse_latex!("1")
+    );
+    assert_eq!(
+        simplify(crate::parse_latex!("\\frac{x}{x}"), &Assumptions::strict()),
+        crate::parse_latex!("\\frac{x}{x}")
+    );
+    assert_eq!(
+        simplify(
+            crate::parse_latex!("\\frac{x}{x}"),
+            &Assumptions::parse("y > 0").unwrap().into_strict()
+        ),
+        crate::parse_latex!("\\frac{x}{x}")
+    );
+    let nonzero = Assumptions::strict().assume("x", Assumption::Nonzero);
+    assert_eq!(
+        simplify(crate::parse_latex!("\\frac{x}{x}"), &nonzero),
+        crate::parse_latex!("1")
+    );
+
+    assert_eq!(
+        Equation::var("x")
+            .abs()
+            .differentiate_with_assumptions(&x, &positive, &mut None),
+        crate::parse_latex!("1")
+    );
+    assert_eq!(
+        crate::parse_latex!("\\frac{1}{x}")
+            .integrate_with_assumptions(&x, &Assumptions::new(), &mut None)
+            .simplify_until_complete(&mut None),
+        Equation::var("x").abs().ln()
+    );
+    assert_eq!(
+        crate::parse_latex!("\\frac{1}{x}")
+            .integrate_with_assumptions(&x, &positive, &mut None)
+            .simplify_until_complete(&mut None),
+        crate::parse_latex!("\\ln(x)")
+    );
+
+    let parsed = Assumptions::parse("m > 0, n \\in \\mathbb{Z}, k ∈ ℝ, q ≠ 0").unwrap();
+    assert_eq!(
+        parsed,
+        Assumptions::new()
+            .assume("m", Assumption::Positive)
+            .assume("n", Assumption::Integer)
+            .assume("k", Assumption::Real)
+            .assume("q", Assumption::Nonzero)
+    );
+    assert_eq!(
+        Assumptions::parse("x > 0, x < 0").unwrap_err().assumption,
+        "x < 0"
+    );
+}
+
+#[test]
+fn simplify_options() {
+    let simplified = crate::parse_latex!("(x^{2})^{3}\\cdot x")
+        .simplify_with_options(&SimplifyOptions::default(), &mut None);
+    assert!(simplified.reached_fixed_point());
+    assert_eq!(simplified.equation, crate::parse_latex!("x^{7}"));
+
+    let one_pass = SimplifyOptions {
+        max_iterations: 1,
+        ..SimplifyOptions::default()
+    };
+    let simplified =
+        crate::parse_latex!("(x^{2})^{3}\\cdot x").simplify_with_options(&one_pass, &mut None);
+    assert_eq!(simplified.convergence, Convergence::MaxIterations);
+
+    let no_time = SimplifyOptions {
+        time_budget: Some(Duration::ZERO),
+        ..SimplifyOptions::default()
+    };
+    let simplified =
+        crate::parse_latex!("(x^{2})^{3}\\cdot x").simplify_with_options(&no_time, &mut None);
+    assert_eq!(simplified.convergence, Convergence::TimeBudget);
+}
+
+#[test]
+fn transforms() {
+    let x = Variable::Letter("x".to_string());
+    let latex = |latex: &str| Equation::from_latex(latex, false);
+
+    let mut step_logger = Some(StepLogger::new());
+    let expanded = latex("(x+1)^{2}").expand(&mut step_logger);
+    assert_eq!(expanded.to_string(), "1+2\\cdot x+x^{2}");
+    assert_eq!(step_logger.unwrap().steps()[0].message(), Some("Expand"));
+    let expanded = latex("(a+b)\\cdot (c-d)").expand(&mut None);
+    assert_eq!(
+        expanded.to_string(),
+        "a\\cdot c+b\\cdot c-a\\cdot d-b\\cdot d"
+    );
+    match latex("(x+1)^{22}").expand(&mut None) {
+        Equation::Addition(terms) => assert_eq!(terms.len(), 23),
+        expanded => panic!("Expected a sum, got {expanded}"),
+    }
+    // The binomial coefficients of the 70th power don't fit an i64
+    assert_eq!(
+        latex("(x+1)^{70}").expand(&mut None).to_string(),
+        "(1+x)^{70}"
+    );
+
+    let factor = |equation: &str| latex(equation).factor(&mut None).to_string();
+    assert_eq!(factor("x^{2}+2\\cdot x+1"), "(x+1)^{2}");
+    assert_eq!(factor("x^{3}-x"), "x\\cdot (x-1)\\cdot (x+1)");
+    assert_eq!(
+        factor("6\\cdot x^{2}+x-1"),
+        "(2\\cdot x+1)\\cdot (3\\cdot x-1)"
+    );
+    assert_eq!(factor("x^{4}-1"), "(x-1)\\cdot (x+1)\\cdot (x^{2}+1)");
+    assert_eq!(factor("2\\cdot a+4\\cdot b"), "2\\cdot (a+2\\cdot b)");
+    assert_eq!(
+        factor("x^{2}-1000000000000000000"),
+        "-1000000000000000000+x^{2}"
+    );
+
+    let collected = latex("a\\cdot x+b\\cdot x^{2}+c\\cdot x+d").collect(&x, &mut None);
+    assert_eq!(collected.to_string(), "d+(a+c)\\cdot x+b\\cdot x^{2}");
+
+    let together = latex("\\frac{1}{x}+\\frac{1}{y}").together(&mut None);
+    assert_eq!(together.to_string(), "\\frac{x+y}{x\\cdot y}");
+
+    let apart = latex("\\frac{1}{x^{2}-1}").apart(&x, &mut None);
+    assert_eq!(
+        apart.to_string(),
+        "\\frac{1}{2\\cdot (x-1)}+\\frac{-1}{2\\cdot (x+1)}"
+    );
+    assert!(approx_equal(apart, latex("\\frac{1}{x^{2}-1}")));
+    let apart = latex("\\frac{1}{(x-1)^{2}\\cdot (x+2)}").apart(&x, &mut None);
+    assert!(approx_equal(
+        apart,
+        latex("\\frac{1}{(x-1)^{2}\\cdot (x+2)}")
+    ));
+    let apart = latex("\\frac{x^{3}}{x^{2}-1}").apart(&x, &mut None);
+    assert!(approx_equal(apart, latex("\\frac{x^{3}}{x^{2}-1}")));
+}
+
+#[test]
+fn trigonometry() {
+    let latex = |latex: &str| Equation::from_latex(latex, false);
+    let simplified = |equation: &str| latex(equation).simplify_until_complete(&mut None);
+
+    assert_eq!(
+        latex("\\sin(\\frac{\\pi}{6})").calculate_exact(),
+        Some(Rational64::new(1, 2))
+    );
+    assert_eq!(
+        latex("\\cos(\\frac{2\\cdot \\pi}{3})").calculate_exact(),
+        Some(Rational64::new(-1, 2))
+    );
+    assert_eq!(latex("\\sin(\\frac{\\pi}{4})").calculate_exact(), None);
+    assert_eq!(
+        simplified("\\cos(\\frac{\\pi}{4})").to_string(),
+        "\\frac{1}{2}\\cdot \\sqrt{2}"
+    );
+    assert_eq!(
+        simplified("\\sin(\\frac{4\\cdot \\pi}{3})").to_string(),
+        "-\\frac{1}{2}\\cdot \\sqrt{3}"
+    );
+    assert_eq!(simplified("\\sin(\\pi)"), crate::parse_latex!("0"));
+    assert_eq!(
+        simplified("\\cos(\\frac{\\pi}{5})").to_string(),
+        "\\cos(\\frac{1}{5}\\cdot \\pi)"
+    );
+
+    assert_eq!(
+        simplified("2\\cdot \\sin(x)^{2}+2\\cdot \\cos(x)^{2}"),
+        crate::parse_latex!("2")
+    );
+    assert_eq!(
+        simplified("y+\\sin(2\\cdot x)^{2}+\\cos(2\\cdot x)^{2}").to_string(),
+        "1+y"
+    );
+    assert_eq!(
+        simplified("\\sin(x)^{2}-\\cos(x)^{2}").to_string(),
+        "\\sin(x)^{2}-\\cos(x)^{2}"
+    );
+
+    let mut step_logger = Some(StepLogger::new());
+    let expanded = latex("\\sin(2\\cdot x)").trig_expand(&mut step_logger);
+    assert_eq!(expanded.to_string(), "2\\cdot \\sin(x)\\cdot \\cos(x)");
+    let step_logger = step_logger.unwrap();
+    let step = &step_logger.steps()[0];
+    assert_eq!(step.message(), Some("Expand trigonometric functions"));
+    assert_eq!(step.sub_steps()[0].message(), Some("Double angle formula"));
+    assert_eq!(
+        latex("\\sin(x+y)").trig_expand(&mut None).to_string(),
+        "\\sin(x)\\cdot \\cos(y)+\\sin(y)\\cdot \\cos(x)"
+    );
+    assert_eq!(
+        latex("\\cos(x-y)").trig_expand(&mut None).to_string(),
+        "\\sin(x)\\cdot \\sin(y)+\\cos(x)\\cdot \\cos(y)"
+    );
+    let expanded = latex("\\sin(3\\cdot x)").trig_expand(&mut None);
+    assert!(approx_equal(expanded, latex("\\sin(3\\cdot x)")));
+    let expanded = latex("\\sin(24\\cdot x)").trig_expand(&mut None);
+    assert!(matches!(expanded, Equation::Addition(_)));
+    assert!(approx_equal(expanded, latex("\\sin(24\\cdot x)")));
+
+    let mut step_logger = Some(StepLogger::new());
+    let reduced = latex("2\\cdot \\sin(x)\\cdot \\cos(x)").trig_reduce(&mut step_logger);
+    assert_eq!(reduced.to_string(), "\\sin(2\\cdot x)");
+    let step_logger = step_logger.unwrap();
+    let step = &step_logger.steps()[0];
+    assert_eq!(
+        step.sub_steps()[0].message(),
+        Some("Product to sum formula")
+    );
+    assert_eq!(
+        latex("\\cos(x)^{2}-\\sin(x)^{2}")
+            .trig_reduce(&mut None)
+            .to_string(),
+        "\\cos(2\\cdot x)"
+    );
+    assert_eq!(
+        latex("\\sin(x)^{2}").trig_reduce(&mut None).to_string(),
+        "\\frac{1}{2}-\\frac{1}{2}\\cdot \\cos(2\\cdot x)"
+    );
+    let reduced = latex("\\cos(x)^{3}").trig_reduce(&mut None);
+    assert!(approx_equal(reduced, latex("\\cos(x)^{3}")));
+}
+
+#[test]
+fn logarithms() {
+    let latex = |latex: &str| Equation::from_latex(latex, false);
+    let positive = Assumptions::new()
+        .assume("a", Assumption::Positive)
+        .assume("b", Assumption::Positive)
+        .assume("c", Assumption::Positive);
+    let simplified = |equation: &str| {
+        latex(equation)
+            .simplify_until_complete_with_assumptions(&positive, &mut None)
+            .to_string()
+    };
+
+    assert_eq!(simplified("\\frac{e^{x}}{e^{y}}"), "e^{x-y}");
+    assert_eq!(simplified("e^{\\ln(a)}"), "a");
+    assert_eq!(simplified("e^{\\ln(x)}"), "e^{\\ln(x)}");
+    assert_eq!(simplified("e^{2\\cdot \\ln(a)}"), "a^{2}");
+    assert_eq!(simplified("e^{\\ln(a)+\\ln(b)}"), "a\\cdot b");
+
+    let mut step_logger = Some(StepLogger::new());
+    let expanded = latex("\\ln(\\frac{a\\cdot b^{2}}{c})").expand_log(&positive, &mut step_logger);
+    assert_eq!(expanded.to_string(), "\\ln(a)+2\\cdot \\ln(b)-\\ln(c)");
+    let step_logger = step_logger.unwrap();
+    let step = &step_logger.steps()[0];
+    assert_eq!(step.message(), Some("Expand logarithms"));
+    assert_eq!(step.sub_steps().len(), 3);
+    let expand = |equation: &str| latex(equation).expand_log(&positive, &mut None).to_string();
+    assert_eq!(expand("\\ln(x\\cdot y)"), "\\ln(x\\cdot y)");
+    assert_eq!(expand("\\ln(a\\cdot x)"), "\\ln(a)+\\ln(x)");
+    assert_eq!(expand("\\ln(\\sqrt{a})"), "\\frac{1}{2}\\cdot \\ln(a)");
+    // Radioactive decay N = N_0 e^{-\\lambda t}
+    assert_eq!(expand("\\ln(a\\cdot e^{-k\\cdot t})"), "\\ln(a)-k\\cdot t");
+
+    let combine = |equation: &str| {
+        latex(equation)
+            .combine_log(&positive, &mut None)
+            .to_string()
+    };
+    assert_eq!(
+        combine("\\ln(a)+2\\cdot \\ln(b)-\\ln(c)"),
+        "\\ln(\\frac{a\\cdot b^{2}}{c})"
+    );
+    assert_eq!(combine("\\ln(x)+\\ln(y)"), "\\ln(x)+\\ln(y)");
+    assert_eq!(combine("2\\cdot \\ln(a)"), "\\ln(a^{2})");
+    assert_eq!(combine("y+\\ln(a)-\\ln(b)"), "y+\\ln(\\frac{a}{b})");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use crate::math::Constant;
+
+    let x = Equation::var("x");
+    let derivative = Equation::Derivative((Box::new((x.clone().ln(), x.clone())), true));
+    let equation = Equation::Equals(Box::new((
+        -(Equation::from(Rational64::new(-3, 4)) * x.clone().pow(Equation::from(2)))
+            + derivative
+            + Equation::Arcsin(Box::new(x.clone()))
+            + Equation::Arccos(Box::new(Equation::from(Constant::E)))
+            + Equation::Arctan(Box::new(Equation::from(Constant::PI))),
+        (x.clone().sin() / x.cos()).abs() + Equation::Variable(Variable::Vector("v".to_string())),
+    )));
+    let json = serde_json::to_string(&equation).unwrap();
+    assert_eq!(serde_json::from_str::<Equation>(&json).unwrap(), equation);
+
+    let rational = |pair: &str| {
+        serde_json::from_str::<Variable>(&format!("{{\"Rational\":{pair}}}")).map_err(|_| ())
+    };
+    assert_eq!(
+        rational("[2,-4]"),
+        Ok(Variable::Rational(Rational64::new(-1, 2)))
+    );
+    assert_eq!(rational("[1,0]"), Err(()));
+    assert_eq!(rational(&format!("[{},-1]", i64::MIN)), Err(()));
+    assert_eq!(rational(&format!("[1,{}]", i64::MIN)), Err(()));
+}